@@ -0,0 +1,586 @@
+extern crate piston_window;
+
+use atlas::Atlas;
+use game::*;
+use piston_window::*;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PlayMode {
+    Once,
+    Loop,
+    PingPong,
+}
+
+impl PlayMode {
+    fn from_manifest_name(name: &str) -> Option<PlayMode> {
+        match name {
+            "once" => Some(PlayMode::Once),
+            "loop" => Some(PlayMode::Loop),
+            "ping_pong" => Some(PlayMode::PingPong),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AnimationEvent {
+    Finished, // A clip that plays once has shown its last frame
+    Looped,   // A looping clip has gone back to its first frame
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Frame {
+    pub rect: [f64; 4], // Source rectangle in the sheet
+    pub duration: f64,
+}
+
+pub struct Clip {
+    pub texture: usize, // Index into the sheets loaded with the clips
+    pub frames: Vec<Frame>,
+    pub mode: PlayMode,
+}
+
+// A clip as written in the manifest, before its sheet has been sliced
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipDef {
+    pub name: String,
+    pub file: String,
+    pub grid: (u32, u32), // (columns, rows)
+    pub frames: Option<Vec<usize>>,
+    pub frame_time: f64,
+    pub durations: Option<Vec<f64>>,
+    pub mode: PlayMode,
+}
+
+impl ClipDef {
+    fn new(name: &str) -> ClipDef {
+        use settings::animation::FRAME_TIME;
+
+        ClipDef {
+            name: name.to_owned(),
+            file: String::new(),
+            grid: (1, 1),
+            frames: None,
+            frame_time: FRAME_TIME,
+            durations: None,
+            mode: PlayMode::Once,
+        }
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "file" => self.file = value.to_owned(),
+            "grid" => self.grid = parse_grid(value)?,
+            "frames" => self.frames = Some(parse_frames(value)?),
+            "frame_time" => {
+                self.frame_time = match value.parse::<f64>() {
+                    Ok(time) if time > 0.0 => time,
+                    _ => return Err(format!("Frame time '{}' must be above zero", value)),
+                }
+            }
+            "durations" => {
+                let durations: Result<Vec<f64>, _> =
+                    value.split(',').map(|d| d.trim().parse::<f64>()).collect();
+                match durations {
+                    Ok(ref durations) if durations.iter().all(|&d| d > 0.0) => {
+                        self.durations = Some(durations.clone())
+                    }
+                    _ => return Err(format!("Bad frame durations '{}'", value)),
+                }
+            }
+            "mode" => {
+                self.mode = PlayMode::from_manifest_name(value)
+                    .ok_or_else(|| format!("Unknown play mode '{}'", value))?
+            }
+            _ => return Err(format!("Unknown clip setting '{}'", name)),
+        }
+        Ok(())
+    }
+
+    // Checks the settings fit together once the whole block has been read
+    fn validate(&self) -> Result<(), String> {
+        let frame_count = (self.grid.0 * self.grid.1) as usize;
+
+        if self.file.is_empty() {
+            return Err(format!("Clip '{}' has no file", self.name));
+        }
+        if let Some(&frame) = self.frame_indices().iter().find(|&&f| f >= frame_count) {
+            return Err(format!(
+                "Clip '{}' uses frame {} but its grid only has {}",
+                self.name, frame, frame_count
+            ));
+        }
+        if let Some(ref durations) = self.durations {
+            if durations.len() != self.frame_indices().len() {
+                return Err(format!(
+                    "Clip '{}' has {} durations for {} frames",
+                    self.name,
+                    durations.len(),
+                    self.frame_indices().len()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn frame_indices(&self) -> Vec<usize> {
+        match self.frames {
+            Some(ref frames) => frames.clone(),
+            None => (0..(self.grid.0 * self.grid.1) as usize).collect(),
+        }
+    }
+
+    fn frame_durations(&self) -> Vec<f64> {
+        match self.durations {
+            Some(ref durations) => durations.clone(),
+            None => vec![self.frame_time; self.frame_indices().len()],
+        }
+    }
+}
+
+// "8x4" is eight columns and four rows
+fn parse_grid(value: &str) -> Result<(u32, u32), String> {
+    let mut parts = value.split('x').map(|p| p.trim().parse::<u32>());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(columns)), Some(Ok(rows)), None) if columns > 0 && rows > 0 => Ok((columns, rows)),
+        _ => Err(format!("Bad grid '{}', expected columns x rows", value)),
+    }
+}
+
+// A list of frame numbers and ranges, like "0, 2, 5-9"
+fn parse_frames(value: &str) -> Result<Vec<usize>, String> {
+    let mut frames = vec![];
+    for part in value.split(',') {
+        let part = part.trim();
+        let range: Vec<_> = part.splitn(2, '-').map(|n| n.trim().parse::<usize>()).collect();
+        match range.as_slice() {
+            [Ok(frame)] => frames.push(*frame),
+            [Ok(first), Ok(last)] if first <= last => frames.extend(*first..=*last),
+            _ => return Err(format!("Bad frame list '{}'", value)),
+        }
+    }
+    Ok(frames)
+}
+
+// The manifest is a list of "[name]" blocks, each followed by "setting = value" lines
+pub fn parse_manifest(contents: &str) -> (Vec<ClipDef>, Vec<String>) {
+    let mut clips: Vec<ClipDef> = vec![];
+    let mut errors = vec![];
+
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            let name = line[1..line.len() - 1].trim();
+            if clips.iter().any(|c| c.name == name) {
+                errors.push(format!("Line {}: clip '{}' is defined twice", line_no + 1, name));
+            }
+            clips.push(ClipDef::new(name));
+            continue;
+        }
+
+        let mut fields = line.splitn(2, '=');
+        let result = match (clips.last_mut(), fields.next(), fields.next()) {
+            (None, _, _) => Err("Setting outside of a [clip] block".to_owned()),
+            (Some(clip), Some(name), Some(value)) => clip.set(name.trim(), value.trim()),
+            _ => Err("Expected 'name = value'".to_owned()),
+        };
+        if let Err(err) = result {
+            errors.push(format!("Line {}: {}", line_no + 1, err));
+        }
+    }
+
+    for clip in clips.iter() {
+        if let Err(err) = clip.validate() {
+            errors.push(err);
+        }
+    }
+
+    (clips, errors)
+}
+
+// Every clip in the manifest along with the sheets they are cut from. Clips of images in the
+// sprite atlas are cut from the atlas instead.
+pub struct Clips {
+    textures: Vec<Rc<G2dTexture>>,
+    clips: Vec<(String, Rc<Clip>)>,
+    placeholder: Rc<Clip>, // Shown in place of clips that aren't in the manifest
+}
+
+impl Clips {
+    pub fn load(
+        window: &mut PistonWindow,
+        folder: &PathBuf,
+        manifest: &str,
+        atlas: &Atlas,
+    ) -> Result<Clips, Vec<String>> {
+        use settings::animation::FRAME_TIME;
+
+        let contents = fs::read_to_string(folder.join(manifest))
+            .map_err(|err| vec![format!("Could not read {}: {}", manifest, err)])?;
+        let (defs, errors) = parse_manifest(&contents);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut files: Vec<String> = vec![];
+        let mut textures = vec![atlas.texture()];
+        let mut clips = vec![];
+
+        for def in defs {
+            // Clips cut from the same sheet share its texture
+            let (texture, region) = match atlas.region(&def.file) {
+                Some(region) => (0, region),
+                None => {
+                    let texture = match files.iter().position(|f| *f == def.file) {
+                        Some(idx) => idx + 1,
+                        None => {
+                            textures.push(Rc::new(load_texture(window, folder, &def.file)));
+                            files.push(def.file.clone());
+                            textures.len() - 1
+                        }
+                    };
+                    let (width, height) = textures[texture].get_size();
+                    (texture, [0.0, 0.0, width as f64, height as f64])
+                }
+            };
+
+            let (columns, rows) = def.grid;
+            let frame_width = (region[2] as u32 / columns) as f64;
+            let frame_height = (region[3] as u32 / rows) as f64;
+
+            let frames = def.frame_indices()
+                .iter()
+                .zip(def.frame_durations().iter())
+                .map(|(&idx, &duration)| Frame {
+                    rect: [
+                        region[0] + (idx as u32 % columns) as f64 * frame_width,
+                        region[1] + (idx as u32 / columns) as f64 * frame_height,
+                        frame_width,
+                        frame_height,
+                    ],
+                    duration,
+                })
+                .collect();
+
+            clips.push((
+                def.name.clone(),
+                Rc::new(Clip {
+                    texture,
+                    frames,
+                    mode: def.mode,
+                }),
+            ));
+        }
+
+        let placeholder = Rc::new(Clip {
+            texture: 0,
+            frames: vec![Frame {
+                rect: atlas.placeholder(),
+                duration: FRAME_TIME,
+            }],
+            mode: PlayMode::Loop,
+        });
+
+        Ok(Clips {
+            textures,
+            clips,
+            placeholder,
+        })
+    }
+
+    // The named clip, or a placeholder if the manifest has no clip by that name. Missing clips
+    // are reported by the validation pass, so they are skipped here.
+    pub fn get(&self, name: &str) -> Rc<Clip> {
+        match self.clips.iter().find(|(n, _)| n == name) {
+            Some((_, clip)) => clip.clone(),
+            None => self.placeholder.clone(),
+        }
+    }
+
+    fn texture(&self, idx: usize) -> &G2dTexture {
+        &self.textures[idx]
+    }
+}
+
+// Where playback is within a clip
+#[derive(Debug, Copy, Clone)]
+struct Playhead {
+    frame: usize,
+    time: f64,
+    forward: bool,
+    playing: bool,
+}
+
+impl Playhead {
+    fn new() -> Playhead {
+        Playhead {
+            frame: 0,
+            time: 0.0,
+            forward: true,
+            playing: false,
+        }
+    }
+
+    fn advance(&mut self, clip: &Clip, dt: f64) -> Option<AnimationEvent> {
+        if !self.playing {
+            return None;
+        }
+
+        let mut event = None;
+        self.time += dt;
+        while self.playing && self.time >= clip.frames[self.frame].duration {
+            self.time -= clip.frames[self.frame].duration;
+            if let Some(e) = self.step(clip) {
+                event = Some(e);
+            }
+        }
+        event
+    }
+
+    fn step(&mut self, clip: &Clip) -> Option<AnimationEvent> {
+        let last = clip.frames.len() - 1;
+
+        match clip.mode {
+            PlayMode::Once => {
+                if self.frame < last {
+                    self.frame += 1;
+                    None
+                } else {
+                    self.playing = false;
+                    Some(AnimationEvent::Finished)
+                }
+            }
+            PlayMode::PingPong if last > 0 => {
+                if self.forward && self.frame == last {
+                    self.forward = false;
+                } else if !self.forward && self.frame == 0 {
+                    self.forward = true;
+                }
+
+                if self.forward {
+                    self.frame += 1;
+                    None
+                } else {
+                    self.frame -= 1;
+                    if self.frame == 0 {
+                        Some(AnimationEvent::Looped)
+                    } else {
+                        None
+                    }
+                }
+            }
+            PlayMode::Loop | PlayMode::PingPong => {
+                if self.frame < last {
+                    self.frame += 1;
+                    None
+                } else {
+                    self.frame = 0;
+                    Some(AnimationEvent::Looped)
+                }
+            }
+        }
+    }
+}
+
+pub struct Animation {
+    clip: Rc<Clip>,
+    playhead: Playhead,
+    pos: Point,
+    scale: f64,
+    rotation: f64, // Degrees
+    opacity: f32,
+    speed: f64, // Playback speed, 1 is normal
+}
+
+impl Animation {
+    pub fn new(clip: Rc<Clip>, scale: f64) -> Animation {
+        Animation {
+            clip,
+            playhead: Playhead::new(),
+            pos: Point::new(0.0, 0.0),
+            scale,
+            rotation: 0.0,
+            opacity: 1.0,
+            speed: 1.0,
+        }
+    }
+
+    pub fn draw(&self, clips: &Clips, c: piston_window::Context, g: &mut G2d) -> () {
+        self.draw_at_pos(clips, self.pos, 1.0, c, g);
+    }
+
+    pub fn draw_at_pos(
+        &self,
+        clips: &Clips,
+        pos: Point,
+        scale: f64,
+        c: piston_window::Context,
+        g: &mut G2d,
+    ) -> () {
+        if !self.playhead.playing {
+            return;
+        }
+        let frame = self.clip.frames[self.playhead.frame];
+        let final_scale = self.scale * scale;
+        Image::new()
+            .src_rect(frame.rect)
+            .color([1.0, 1.0, 1.0, self.opacity])
+            .draw(
+                clips.texture(self.clip.texture),
+                &c.draw_state,
+                c.transform
+                    .trans(pos.x, pos.y)
+                    .rot_deg(self.rotation)
+                    .zoom(final_scale)
+                    .trans(-0.5 * frame.rect[2], -0.5 * frame.rect[3]),
+                g,
+            );
+    }
+
+    // Returns an event when the clip finishes or loops
+    pub fn update(&mut self, dt: f64) -> Option<AnimationEvent> {
+        self.playhead.advance(&self.clip, dt * self.speed)
+    }
+
+    pub fn play(&mut self) -> () {
+        self.playhead = Playhead::new();
+        self.playhead.playing = true;
+    }
+
+    pub fn stop(&mut self) -> () {
+        self.playhead = Playhead::new();
+    }
+
+    // Switches clip, starting it from the beginning if it wasn't already playing
+    pub fn set_clip(&mut self, clip: &Rc<Clip>) -> () {
+        if !Rc::ptr_eq(&self.clip, clip) {
+            self.clip = clip.clone();
+            self.play();
+        }
+    }
+
+    pub fn set_pos(&mut self, pos: Point) -> () {
+        self.pos = pos;
+    }
+
+    pub fn set_rotation(&mut self, rotation: f64) -> () {
+        self.rotation = rotation;
+    }
+
+    pub fn set_scale(&mut self, scale: f64) -> () {
+        self.scale = scale;
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) -> () {
+        self.opacity = opacity;
+    }
+
+    pub fn set_speed(&mut self, speed: f64) -> () {
+        self.speed = speed;
+    }
+}
+
+// A clip with blank frames, for tests that don't draw anything
+#[cfg(test)]
+pub fn test_clip(durations: &[f64], mode: PlayMode) -> Rc<Clip> {
+    Rc::new(Clip {
+        texture: 0,
+        frames: durations
+            .iter()
+            .map(|&duration| Frame {
+                rect: [0.0; 4],
+                duration,
+            })
+            .collect(),
+        mode,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_finish_clip_played_once() {
+        let mut animation = Animation::new(test_clip(&[0.1, 0.2], PlayMode::Once), 1.0);
+        animation.play();
+        assert_eq!(animation.update(0.15), None);
+        assert_eq!(animation.playhead.frame, 1);
+        assert_eq!(animation.update(0.2), Some(AnimationEvent::Finished));
+        assert!(!animation.playhead.playing);
+    }
+
+    #[test]
+    fn it_should_loop_clip() {
+        let mut animation = Animation::new(test_clip(&[0.1, 0.1], PlayMode::Loop), 1.0);
+        animation.play();
+        assert_eq!(animation.update(0.25), Some(AnimationEvent::Looped));
+        assert_eq!(animation.playhead.frame, 0);
+        assert!(animation.playhead.playing);
+    }
+
+    #[test]
+    fn it_should_ping_pong_clip() {
+        let mut animation = Animation::new(test_clip(&[0.1, 0.1, 0.1], PlayMode::PingPong), 1.0);
+        animation.play();
+        let mut frames = vec![];
+        for _ in 0..5 {
+            animation.update(0.1);
+            frames.push(animation.playhead.frame);
+        }
+        assert_eq!(frames, vec![1, 2, 1, 0, 1]);
+    }
+
+    #[test]
+    fn it_should_scale_playback_speed() {
+        let mut animation = Animation::new(test_clip(&[0.1, 0.1], PlayMode::Once), 1.0);
+        animation.set_speed(2.0);
+        animation.play();
+        animation.update(0.05);
+        assert_eq!(animation.playhead.frame, 1);
+    }
+
+    #[test]
+    fn it_should_parse_manifest() {
+        let (clips, errors) = parse_manifest(
+            "# Comment\n[boom]\nfile = boom.png\ngrid = 4x2\nframes = 0-2, 7\n\
+             durations = 0.1, 0.1, 0.2, 0.5\nmode = ping_pong\n",
+        );
+        assert!(errors.is_empty());
+        assert_eq!(clips[0].name, "boom");
+        assert_eq!(clips[0].grid, (4, 2));
+        assert_eq!(clips[0].frame_indices(), vec![0, 1, 2, 7]);
+        assert_eq!(clips[0].frame_durations(), vec![0.1, 0.1, 0.2, 0.5]);
+        assert_eq!(clips[0].mode, PlayMode::PingPong);
+    }
+
+    #[test]
+    fn it_should_report_manifest_errors() {
+        let (_, errors) = parse_manifest(
+            "file = orphan.png\n[a]\nfile = a.png\ngrid = 2x2\nframes = 5\n\
+             [b]\nmode = backwards\n[a]\nfile = a.png\n",
+        );
+        // Orphan setting, bad mode, duplicate name, frame out of range, missing file
+        assert_eq!(errors.len(), 5);
+    }
+
+    #[test]
+    fn it_should_fall_back_to_placeholder_clip() {
+        let placeholder = test_clip(&[1.0], PlayMode::Loop);
+        let clips = Clips {
+            textures: vec![],
+            clips: vec![("ship".to_owned(), test_clip(&[0.5], PlayMode::Once))],
+            placeholder: placeholder.clone(),
+        };
+
+        assert_eq!(clips.get("ship").mode, PlayMode::Once);
+        assert!(Rc::ptr_eq(&clips.get("no_such_clip"), &placeholder));
+    }
+}
@@ -0,0 +1,371 @@
+extern crate image;
+extern crate piston_window;
+
+use animation::{parse_manifest, ClipDef};
+use background::parse_themes;
+use locale::Locale;
+use piston_window::*;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AssetKind {
+    Texture,
+    Sprite, // A small image packed into the sprite atlas
+    Sheet, // A texture cut into animation frames
+    Font,
+    Locale, // A table of UI text in one language
+}
+
+const ASSET_KINDS: [AssetKind; 5] = [
+    AssetKind::Texture,
+    AssetKind::Sprite,
+    AssetKind::Sheet,
+    AssetKind::Font,
+    AssetKind::Locale,
+];
+
+impl AssetKind {
+    fn manifest_name(&self) -> &'static str {
+        match *self {
+            AssetKind::Texture => "texture",
+            AssetKind::Sprite => "sprite",
+            AssetKind::Sheet => "sheet",
+            AssetKind::Font => "font",
+            AssetKind::Locale => "locale",
+        }
+    }
+
+    fn from_manifest_name(name: &str) -> Option<AssetKind> {
+        ASSET_KINDS.iter().find(|k| k.manifest_name() == name).cloned()
+    }
+}
+
+// Every file the game loads from the assets folder, as "kind = path" lines
+pub struct AssetManifest {
+    entries: Vec<(AssetKind, String)>,
+}
+
+impl AssetManifest {
+    pub fn parse(contents: &str) -> (AssetManifest, Vec<String>) {
+        let mut entries = vec![];
+        let mut errors = vec![];
+
+        for (line_no, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.splitn(2, '=');
+            match (fields.next(), fields.next()) {
+                (Some(kind), Some(file)) => match AssetKind::from_manifest_name(kind.trim()) {
+                    Some(kind) => entries.push((kind, file.trim().to_owned())),
+                    None => errors.push(format!(
+                        "Line {}: unknown asset kind '{}'",
+                        line_no + 1,
+                        kind.trim()
+                    )),
+                },
+                _ => errors.push(format!("Line {}: expected 'kind = file'", line_no + 1)),
+            }
+        }
+
+        (AssetManifest { entries }, errors)
+    }
+
+    // Manifest problems are reported by the validation pass, so they are skipped here
+    pub fn load(folder: &Path) -> AssetManifest {
+        use settings::assets::MANIFEST;
+
+        let contents = fs::read_to_string(folder.join(MANIFEST)).unwrap_or_default();
+        AssetManifest::parse(&contents).0
+    }
+
+    pub fn files(&self, kind: AssetKind) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, file)| file.clone())
+            .collect()
+    }
+
+    fn kind_of(&self, file: &str) -> Option<AssetKind> {
+        self.entries
+            .iter()
+            .find(|(_, f)| f == file)
+            .map(|&(kind, _)| kind)
+    }
+}
+
+// Everything wrong with the assets, found in one pass so it can all be fixed at once
+pub struct Report {
+    pub problems: Vec<String>,
+    pub fatal: bool, // Missing fonts or unreadable manifests can't be papered over
+}
+
+// Checks every file in the asset manifest can be read, that the animation sheets split evenly
+// into their grids, and that every file and clip the game uses is listed as what it's used as
+pub fn validate(folder: &Path, used: &[(AssetKind, &str)], used_clips: &[&str]) -> Report {
+    use settings::animation;
+    use settings::assets::MANIFEST;
+    use settings::background::THEMES;
+
+    let mut report = Report {
+        problems: vec![],
+        fatal: false,
+    };
+
+    let manifest = match fs::read_to_string(folder.join(MANIFEST)) {
+        Ok(contents) => {
+            let (manifest, errors) = AssetManifest::parse(&contents);
+            report.add_all(MANIFEST, errors, true);
+            manifest
+        }
+        Err(err) => {
+            report.add(format!("Could not read {}: {}", MANIFEST, err), true);
+            AssetManifest { entries: vec![] }
+        }
+    };
+
+    let clips = match fs::read_to_string(folder.join(animation::MANIFEST)) {
+        Ok(contents) => {
+            let (clips, errors) = parse_manifest(&contents);
+            report.add_all(animation::MANIFEST, errors, true);
+            clips
+        }
+        Err(err) => {
+            let problem = format!("Could not read {}: {}", animation::MANIFEST, err);
+            report.add(problem, true);
+            vec![]
+        }
+    };
+
+    let themes = match fs::read_to_string(folder.join(THEMES)) {
+        Ok(contents) => {
+            let (themes, errors) = parse_themes(&contents);
+            report.add_all(THEMES, errors, true);
+            themes
+        }
+        Err(err) => {
+            report.add(format!("Could not read {}: {}", THEMES, err), true);
+            vec![]
+        }
+    };
+
+    for &(kind, ref file) in manifest.entries.iter() {
+        let path = folder.join(file);
+        match kind {
+            AssetKind::Texture | AssetKind::Sprite | AssetKind::Sheet => match image::open(&path) {
+                Ok(img) => {
+                    let size = img.to_rgba().dimensions();
+                    for clip in clips.iter().filter(|c| c.file == *file) {
+                        if let Err(err) = check_grid(clip, size) {
+                            report.add(err, false);
+                        }
+                    }
+                }
+                Err(err) => report.add(format!("Could not load {}: {}", file, err), false),
+            },
+            AssetKind::Font => {
+                let result = fs::read(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|bytes| check_font(&bytes));
+                if let Err(err) = result {
+                    report.add(format!("Could not load {}: {}", file, err), true);
+                }
+            }
+            AssetKind::Locale => match fs::read_to_string(&path) {
+                Ok(contents) => {
+                    let (locale, errors) = Locale::parse("", &contents);
+                    report.add_all(file, errors, false);
+                    for font in locale.fonts() {
+                        if manifest.kind_of(font) != Some(AssetKind::Font) {
+                            let problem = format!("{} isn't listed as a font", font);
+                            report.add(format!("{}: {}", file, problem), false);
+                        }
+                    }
+                }
+                Err(err) => report.add(format!("Could not read {}: {}", file, err), false),
+            },
+        }
+    }
+
+    for clip in clips.iter() {
+        match manifest.kind_of(&clip.file) {
+            Some(AssetKind::Texture) | Some(AssetKind::Sprite) | Some(AssetKind::Sheet) => {}
+            _ => {
+                let problem = format!("{}, used by clip '{}', isn't listed", clip.file, clip.name);
+                report.add(format!("{}: {}", MANIFEST, problem), false);
+            }
+        }
+    }
+    for theme in themes.iter() {
+        for (file, _) in theme.layers.iter() {
+            if manifest.kind_of(file) != Some(AssetKind::Texture) {
+                report.add(
+                    format!("{}, used by theme '{}', isn't listed as a texture", file, theme.name),
+                    false,
+                );
+            }
+        }
+    }
+    for (kind, file) in unlisted(&manifest, used) {
+        let kind_name = kind.manifest_name();
+        let problem = format!("{} isn't listed as a {} in {}", file, kind_name, MANIFEST);
+        // Text can't be drawn at all without the main font
+        report.add(problem, kind == AssetKind::Font);
+    }
+    for name in missing_clips(&clips, used_clips) {
+        let problem = format!("No clip named '{}', using a placeholder", name);
+        report.add(format!("{}: {}", animation::MANIFEST, problem), false);
+    }
+
+    report
+}
+
+impl Report {
+    fn add(&mut self, problem: String, fatal: bool) -> () {
+        self.problems.push(problem);
+        self.fatal = self.fatal || fatal;
+    }
+
+    fn add_all(&mut self, file: &str, problems: Vec<String>, fatal: bool) -> () {
+        for problem in problems {
+            self.add(format!("{}: {}", file, problem), fatal);
+        }
+    }
+}
+
+fn check_grid(clip: &ClipDef, (width, height): (u32, u32)) -> Result<(), String> {
+    let (columns, rows) = clip.grid;
+    if width % columns != 0 || height % rows != 0 {
+        return Err(format!(
+            "{} is {}x{}, which doesn't split into the {}x{} grid of clip '{}'",
+            clip.file, width, height, columns, rows, clip.name
+        ));
+    }
+    Ok(())
+}
+
+// Files the game uses that the manifest leaves out or lists as something else, such as sprites
+// that would never be packed into the atlas
+fn unlisted<'a>(
+    manifest: &AssetManifest,
+    used: &[(AssetKind, &'a str)],
+) -> Vec<(AssetKind, &'a str)> {
+    used.iter()
+        .filter(|&&(kind, file)| manifest.kind_of(file) != Some(kind))
+        .cloned()
+        .collect()
+}
+
+fn missing_clips<'a>(clips: &[ClipDef], used: &[&'a str]) -> Vec<&'a str> {
+    used.iter()
+        .filter(|&&name| !clips.iter().any(|clip| clip.name == name))
+        .cloned()
+        .collect()
+}
+
+// TrueType, OpenType and font collection files all start with one of these tags
+fn check_font(bytes: &[u8]) -> Result<(), String> {
+    let tags: [&[u8]; 4] = [b"\x00\x01\x00\x00", b"OTTO", b"true", b"ttcf"];
+    if tags.iter().any(|tag| bytes.starts_with(tag)) {
+        Ok(())
+    } else {
+        Err("not a TrueType or OpenType font".to_owned())
+    }
+}
+
+// A loud checkerboard that stands in for textures that couldn't be loaded
+pub fn placeholder_texture(window: &mut PistonWindow) -> G2dTexture {
+    Texture::from_image(&mut window.factory, &placeholder_image(), &TextureSettings::new())
+        .unwrap()
+}
+
+pub fn placeholder_image() -> image::RgbaImage {
+    use settings::assets::PLACEHOLDER_SIZE;
+
+    let mut img = image::RgbaImage::new(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE);
+    for x in 0..PLACEHOLDER_SIZE {
+        for y in 0..PLACEHOLDER_SIZE {
+            img.put_pixel(x, y, image::Rgba(placeholder_pixel(x, y)));
+        }
+    }
+    img
+}
+
+fn placeholder_pixel(x: u32, y: u32) -> [u8; 4] {
+    use settings::assets::{PLACEHOLDER_CHECK, PLACEHOLDER_COLORS};
+
+    PLACEHOLDER_COLORS[((x / PLACEHOLDER_CHECK + y / PLACEHOLDER_CHECK) % 2) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_manifest() {
+        let (manifest, errors) =
+            AssetManifest::parse("# Comment\ntexture = a.png\n\nsheet = b.png\nsound = c.ogg\nd\n");
+        assert_eq!(manifest.kind_of("a.png"), Some(AssetKind::Texture));
+        assert_eq!(manifest.kind_of("b.png"), Some(AssetKind::Sheet));
+        assert_eq!(manifest.kind_of("c.ogg"), None);
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn it_should_round_trip_kind_names() {
+        for kind in ASSET_KINDS.iter() {
+            assert_eq!(AssetKind::from_manifest_name(kind.manifest_name()), Some(*kind));
+        }
+    }
+
+    #[test]
+    fn it_should_find_used_files_not_listed_as_their_kind() {
+        let (manifest, _) = AssetManifest::parse("sprite = a.png\ntexture = b.png\n");
+        let used = [
+            (AssetKind::Sprite, "a.png"),
+            (AssetKind::Sprite, "b.png"),
+            (AssetKind::Font, "c.ttf"),
+        ];
+        assert_eq!(
+            unlisted(&manifest, &used),
+            vec![(AssetKind::Sprite, "b.png"), (AssetKind::Font, "c.ttf")]
+        );
+    }
+
+    #[test]
+    fn it_should_check_sheet_splits_into_grid() {
+        let (clips, _) = parse_manifest("[boom]\nfile = boom.png\ngrid = 8x4\n");
+        assert!(check_grid(&clips[0], (256, 128)).is_ok());
+        assert!(check_grid(&clips[0], (256, 130)).is_err());
+    }
+
+    #[test]
+    fn it_should_find_clips_missing_from_manifest() {
+        let (clips, _) = parse_manifest("[boom]\nfile = boom.png\ngrid = 8x4\n");
+        assert_eq!(missing_clips(&clips, &["boom", "star"]), vec!["star"]);
+    }
+
+    #[test]
+    fn it_should_recognise_fonts() {
+        assert!(check_font(b"\x00\x01\x00\x00rest of font").is_ok());
+        assert!(check_font(b"OTTOrest of font").is_ok());
+        assert!(check_font(b"\x89PNG").is_err());
+        assert!(check_font(b"").is_err());
+    }
+
+    #[test]
+    fn it_should_checker_placeholder() {
+        use settings::assets::{PLACEHOLDER_CHECK, PLACEHOLDER_COLORS};
+
+        assert_eq!(placeholder_pixel(0, 0), PLACEHOLDER_COLORS[0]);
+        assert_eq!(placeholder_pixel(PLACEHOLDER_CHECK, 0), PLACEHOLDER_COLORS[1]);
+        assert_eq!(
+            placeholder_pixel(PLACEHOLDER_CHECK, PLACEHOLDER_CHECK),
+            PLACEHOLDER_COLORS[0]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+extern crate image;
+extern crate piston_window;
+
+use assets::placeholder_image;
+use image::{GenericImage, RgbaImage};
+use piston_window::*;
+use sprite::Sprite;
+use std::path::Path;
+use std::rc::Rc;
+
+// Small images packed into one texture, so drawing them doesn't keep switching textures
+pub struct Atlas {
+    texture: Rc<G2dTexture>,
+    regions: Vec<(String, [f64; 4])>,
+    placeholder: [f64; 4], // Checkerboard drawn for anything asked for that isn't packed
+}
+
+impl Atlas {
+    pub fn build(window: &mut PistonWindow, folder: &Path, files: &[String]) -> Atlas {
+        use settings::atlas::{MAX_WIDTH, PADDING};
+
+        // Missing files have already been reported by the asset validation pass. The last image
+        // is the placeholder itself.
+        let images: Vec<RgbaImage> = files
+            .iter()
+            .map(|file| match image::open(folder.join(file)) {
+                Ok(img) => img.to_rgba(),
+                Err(_) => placeholder_image(),
+            })
+            .chain(Some(placeholder_image()))
+            .collect();
+        let sizes: Vec<_> = images.iter().map(|img| img.dimensions()).collect();
+        let (positions, (width, height)) = pack(&sizes, MAX_WIDTH, PADDING);
+
+        let mut atlas = RgbaImage::new(width.max(1), height.max(1));
+        for (img, &(x, y)) in images.iter().zip(positions.iter()) {
+            atlas.copy_from(img, x, y);
+        }
+
+        let texture = Texture::from_image(&mut window.factory, &atlas, &TextureSettings::new())
+            .unwrap();
+        let mut regions: Vec<_> = positions
+            .iter()
+            .zip(sizes.iter())
+            .map(|(&(x, y), &(w, h))| [x as f64, y as f64, w as f64, h as f64])
+            .collect();
+        let placeholder = regions.pop().unwrap();
+
+        Atlas {
+            texture: Rc::new(texture),
+            regions: files.iter().cloned().zip(regions).collect(),
+            placeholder,
+        }
+    }
+
+    pub fn texture(&self) -> Rc<G2dTexture> {
+        self.texture.clone()
+    }
+
+    // Where the file's image is in the atlas
+    pub fn region(&self, file: &str) -> Option<[f64; 4]> {
+        self.regions
+            .iter()
+            .find(|(f, _)| f == file)
+            .map(|&(_, region)| region)
+    }
+
+    pub fn placeholder(&self) -> [f64; 4] {
+        self.placeholder
+    }
+
+    // A sprite of the file's image, or of the placeholder if it was never packed. Sprites the
+    // manifest doesn't list are reported by the validation pass, so they are skipped here.
+    pub fn sprite(&self, file: &str, scale: f64) -> Sprite<G2dTexture> {
+        let region = self.region(file).unwrap_or(self.placeholder);
+        let mut spr = Sprite::from_texture_rect(self.texture.clone(), region);
+        spr.set_scale(scale, scale);
+        spr
+    }
+}
+
+// Lays rectangles out in rows, tallest first, no wider than the maximum width. Returns the top
+// left corner of each rectangle, in the order given, and the size of the whole atlas.
+fn pack(sizes: &[(u32, u32)], max_width: u32, padding: u32) -> (Vec<(u32, u32)>, (u32, u32)) {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|&a, &b| sizes[b].1.cmp(&sizes[a].1));
+
+    let mut positions = vec![(0, 0); sizes.len()];
+    let (mut x, mut y) = (0, 0);
+    let mut row_height = 0;
+    let mut width = 0;
+
+    for idx in order {
+        let (w, h) = sizes[idx];
+        // Anything wider than the atlas gets a row to itself
+        if x > 0 && x + w > max_width {
+            x = 0;
+            y += row_height + padding;
+            row_height = 0;
+        }
+        positions[idx] = (x, y);
+        width = width.max(x + w);
+        row_height = row_height.max(h);
+        x += w + padding;
+    }
+
+    (positions, (width, y + row_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlaps(a: ((u32, u32), (u32, u32)), b: ((u32, u32), (u32, u32))) -> bool {
+        let (((ax, ay), (aw, ah)), ((bx, by), (bw, bh))) = (a, b);
+        ax < bx + bw && bx < ax + aw && ay < by + bh && by < ay + ah
+    }
+
+    #[test]
+    fn it_should_pack_without_overlaps() {
+        let sizes = [(38, 50), (39, 45), (39, 45), (35, 20), (77, 77), (136, 136)];
+        let (positions, (width, height)) = pack(&sizes, 200, 2);
+
+        for i in 0..sizes.len() {
+            let (x, y) = positions[i];
+            assert!(x + sizes[i].0 <= width && y + sizes[i].1 <= height);
+            for j in (i + 1)..sizes.len() {
+                assert!(!overlaps((positions[i], sizes[i]), (positions[j], sizes[j])));
+            }
+        }
+        assert!(width <= 200);
+    }
+
+    #[test]
+    fn it_should_start_new_row_when_full() {
+        let (positions, (width, height)) = pack(&[(60, 10), (60, 10)], 100, 2);
+        assert_eq!(positions, vec![(0, 0), (0, 12)]);
+        assert_eq!((width, height), (60, 22));
+    }
+
+    #[test]
+    fn it_should_pack_nothing() {
+        let (positions, size) = pack(&[], 100, 2);
+        assert!(positions.is_empty());
+        assert_eq!(size, (0, 0));
+    }
+}
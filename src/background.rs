@@ -1,92 +1,683 @@
 extern crate piston_window;
 
+use camera::Camera;
 use game::*;
 use piston_window::*;
-use player::*;
 use sprite::*;
+use std::rc::Rc;
+use tween::*;
+use viewport::Viewport;
 
-pub struct Background(Vec<BGLayer>);
+// A set of image layers, as (file, parallax factor), along with how they are tinted and how fast
+// they scroll
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub layers: Vec<(String, f64)>,
+    pub tint: [f32; 3],
+    pub scroll: f64, // Multiplies every layer's parallax factor
+    pub from: f64,   // Seconds survived before the theme takes over
+    pub speeds: Option<(u32, u32)>, // Game speeds whose runs start on this theme
+}
+
+impl Theme {
+    fn new(name: &str) -> Theme {
+        Theme {
+            name: name.to_owned(),
+            layers: vec![],
+            tint: [1.0, 1.0, 1.0],
+            scroll: 1.0,
+            from: 0.0,
+            speeds: None,
+        }
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "layer" => {
+                let mut fields = value.splitn(2, ',').map(|f| f.trim());
+                match (fields.next(), fields.next().map(|f| f.parse::<f64>())) {
+                    (Some(file), Some(Ok(factor))) if !file.is_empty() => {
+                        self.layers.push((file.to_owned(), factor))
+                    }
+                    _ => return Err(format!("Bad layer '{}', expected file, factor", value)),
+                }
+            }
+            "tint" => {
+                let tint: Vec<_> = value.split(',').map(|c| c.trim().parse::<f32>()).collect();
+                match tint.as_slice() {
+                    [Ok(r), Ok(g), Ok(b)] => self.tint = [*r, *g, *b],
+                    _ => return Err(format!("Bad tint '{}', expected red, green, blue", value)),
+                }
+            }
+            "scroll" => {
+                self.scroll = value
+                    .parse::<f64>()
+                    .map_err(|_| format!("Bad scroll '{}'", value))?
+            }
+            "from" => {
+                self.from = match value.parse::<f64>() {
+                    Ok(time) if time >= 0.0 => time,
+                    _ => return Err(format!("Bad start time '{}'", value)),
+                }
+            }
+            "speeds" => {
+                let speeds: Vec<_> = value
+                    .splitn(2, '-')
+                    .map(|s| s.trim().parse::<u32>())
+                    .collect();
+                self.speeds = match speeds.as_slice() {
+                    [Ok(speed)] => Some((*speed, *speed)),
+                    [Ok(min), Ok(max)] if min <= max => Some((*min, *max)),
+                    _ => return Err(format!("Bad speed range '{}'", value)),
+                };
+            }
+            _ => return Err(format!("Unknown theme setting '{}'", name)),
+        }
+        Ok(())
+    }
+}
+
+// The themes file is a list of "[name]" blocks, each followed by "setting = value" lines
+pub fn parse_themes(contents: &str) -> (Vec<Theme>, Vec<String>) {
+    let mut themes: Vec<Theme> = vec![];
+    let mut errors = vec![];
+
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            let name = line[1..line.len() - 1].trim();
+            if themes.iter().any(|t| t.name == name) {
+                errors.push(format!("Line {}: theme '{}' is defined twice", line_no + 1, name));
+            }
+            themes.push(Theme::new(name));
+            continue;
+        }
+
+        let mut fields = line.splitn(2, '=');
+        let result = match (themes.last_mut(), fields.next(), fields.next()) {
+            (None, _, _) => Err("Setting outside of a [theme] block".to_owned()),
+            (Some(theme), Some(name), Some(value)) => theme.set(name.trim(), value.trim()),
+            _ => Err("Expected 'name = value'".to_owned()),
+        };
+        if let Err(err) = result {
+            errors.push(format!("Line {}: {}", line_no + 1, err));
+        }
+    }
+
+    // Themes take over one after another, starting with the first
+    if themes.is_empty() {
+        errors.push("No themes".to_owned());
+    }
+    for (i, theme) in themes.iter().enumerate() {
+        if theme.layers.is_empty() {
+            errors.push(format!("Theme '{}' has no layers", theme.name));
+        }
+        if i == 0 && theme.from != 0.0 {
+            errors.push(format!("Theme '{}' comes first so must start from 0", theme.name));
+        }
+        if i > 0 && theme.from <= themes[i - 1].from {
+            errors.push(format!(
+                "Theme '{}' must start later than '{}'",
+                theme.name,
+                themes[i - 1].name
+            ));
+        }
+    }
+
+    (themes, errors)
+}
+
+// Problems with the file are reported by the validation pass, so they are skipped here
+pub fn load_themes(folder: &::std::path::Path) -> Vec<Theme> {
+    use settings::background::THEMES;
+
+    let contents = ::std::fs::read_to_string(folder.join(THEMES)).unwrap_or_default();
+    parse_themes(&contents).0
+}
+
+// A layer of procedurally placed stars. Density is in stars per million square pixels.
+pub struct StarLayerConfig {
+    pub factor: f64,
+    pub density: f64,
+    pub size: (f64, f64),
+    pub opacity: (f64, f64),
+    pub twinkle: f64, // How far a star dims at the bottom of its twinkle, 0 to 1
+    pub twinkle_rate: (f64, f64), // Twinkles per second
+    pub colors: &'static [[f32; 3]],
+}
+
+// Soft clouds of colour behind the stars
+pub struct NebulaConfig {
+    pub factor: f64,
+    pub clouds: u32, // Per tile
+    pub radius: (f64, f64),
+    pub colors: &'static [[f32; 4]],
+}
+
+pub struct Background {
+    layers: Vec<BGLayer>,
+    tint: [f32; 3],
+    time: f64, // Drives the twinkling
+}
+
+// Textures already loaded for other themes, so layers shared between themes are only loaded once
+type TextureCache = Vec<(String, Rc<G2dTexture>)>;
 
 impl Background {
-    pub fn new(
+    fn new(
         window: &mut PistonWindow,
         folder: &::std::path::PathBuf,
-        names_and_factors: &'static [(&str, f64)],
+        theme: &Theme,
+        cache: &mut TextureCache,
     ) -> Background {
         use settings::background::SCALE;
 
         let mut all_bg: Vec<BGLayer> = vec![];
 
-        for (file, factor) in names_and_factors.iter() {
-            let bg = load_sprite(window, folder, file, SCALE);
-            all_bg.push(BGLayer::new(bg, *factor));
+        for (file, factor) in theme.layers.iter() {
+            let texture = match cache.iter().find(|(f, _)| f == file) {
+                Some((_, texture)) => texture.clone(),
+                None => {
+                    let texture = Rc::new(load_texture(window, folder, file));
+                    cache.push((file.clone(), texture.clone()));
+                    texture
+                }
+            };
+            let mut bg = Sprite::from_texture(texture);
+            bg.set_scale(SCALE, SCALE);
+            bg.set_color(theme.tint[0], theme.tint[1], theme.tint[2]);
+
+            let clamp = bg.bounding_box();
+            let clamp = Point::new(clamp[2], clamp[3]);
+            all_bg.push(BGLayer::new(Layer::Image(bg), clamp, factor * theme.scroll));
         }
 
-        Background(all_bg)
+        Background {
+            layers: all_bg,
+            tint: theme.tint,
+            time: 0.0,
+        }
     }
 
-    pub fn update(&mut self, player: &Player, dt: f64) -> () {
-        let Background(ref mut backgrounds) = *self;
-        for bg in backgrounds.iter_mut() {
-            bg.update(player, dt);
+    // A sky generated from the seed, the same seed always giving the same sky
+    pub fn procedural(seed: u64) -> Background {
+        use settings::background::{NEBULAE, STAR_LAYERS, TILE_SIZE};
+
+        let mut rng = SeededRng::new(seed);
+        let clamp = Point::new(TILE_SIZE, TILE_SIZE);
+        let mut layers = vec![];
+
+        for config in NEBULAE.iter() {
+            let clouds = generate_nebula(config, &mut rng);
+            layers.push(BGLayer::new(Layer::Nebula(clouds), clamp, config.factor));
+        }
+        for config in STAR_LAYERS.iter() {
+            let stars = generate_stars(config, &mut rng);
+            let layer = Layer::Stars(stars, config.twinkle);
+            layers.push(BGLayer::new(layer, clamp, config.factor));
         }
+
+        Background {
+            layers,
+            tint: [1.0; 3],
+            time: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) -> () {
+        self.time += dt;
     }
 
-    pub fn draw(&mut self, height: u32, width: u32, context: piston_window::Context, g: &mut G2d) {
-        let Background(ref mut backgrounds) = *self;
-        for bg in &mut backgrounds.iter_mut() {
-            bg.draw(height, width, context, g);
+    pub fn draw(
+        &mut self,
+        camera: &Camera,
+        viewport: &Viewport,
+        context: piston_window::Context,
+        g: &mut G2d,
+    ) {
+        self.draw_faded(camera, viewport, 1.0, context, g);
+    }
+
+    fn draw_faded(
+        &mut self,
+        camera: &Camera,
+        viewport: &Viewport,
+        opacity: f32,
+        context: piston_window::Context,
+        g: &mut G2d,
+    ) {
+        let shake = camera.shake();
+        let context = context.trans(shake.x, shake.y);
+        let tint = [self.tint[0], self.tint[1], self.tint[2], opacity];
+        let time = self.time;
+
+        for bg in self.layers.iter_mut() {
+            bg.draw(camera.pos(), time, tint, viewport, context, g);
         }
     }
 }
 
+// Every theme's background, crossfading from one to the next when the theme changes
+pub struct Scenery {
+    themes: Vec<Background>,
+    tiers: Vec<(f64, Option<(u32, u32)>)>, // (from, speeds) of each theme
+    current: usize,
+    previous: Option<usize>, // Theme still fading out
+    fade: Tween,
+}
+
+impl Scenery {
+    pub fn new(
+        window: &mut PistonWindow,
+        folder: &::std::path::PathBuf,
+        themes: &[Theme],
+    ) -> Scenery {
+        use settings::background::CROSSFADE;
+
+        let mut cache = vec![];
+        Scenery {
+            themes: themes
+                .iter()
+                .map(|theme| Background::new(window, folder, theme, &mut cache))
+                .collect(),
+            tiers: themes.iter().map(|theme| (theme.from, theme.speeds)).collect(),
+            current: 0,
+            previous: None,
+            fade: Tween::new(vec![(0.0, 0.0), (1.0, 1.0)], CROSSFADE, Easing::EaseInOut, false),
+        }
+    }
+
+    // Theme for a run at this game speed that has lasted this long
+    pub fn theme_at(&self, game_speed: u32, time: f64) -> usize {
+        theme_for_time(&self.tiers, start_time(&self.tiers, game_speed) + time)
+    }
+
+    pub fn set_theme(&mut self, theme: usize) -> () {
+        if theme != self.current {
+            self.previous = Some(self.current);
+            self.current = theme;
+            self.fade.reset();
+        }
+    }
+
+    // Changes theme straight away, without a crossfade
+    pub fn snap_to_theme(&mut self, theme: usize) -> () {
+        self.current = theme;
+        self.previous = None;
+        self.fade.stop();
+    }
+
+    pub fn update(&mut self, dt: f64) -> () {
+        self.fade.update(dt);
+        if !self.fade.is_playing() {
+            self.previous = None;
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        camera: &Camera,
+        viewport: &Viewport,
+        context: piston_window::Context,
+        g: &mut G2d,
+    ) -> () {
+        match self.previous {
+            Some(previous) => {
+                let opacity = self.fade.get_val() as f32;
+                self.themes[previous].draw(camera, viewport, context, g);
+                self.themes[self.current].draw_faded(camera, viewport, opacity, context, g);
+            }
+            None => self.themes[self.current].draw(camera, viewport, context, g),
+        }
+    }
+}
+
+// Theme for the difficulty tier reached after surviving this long
+fn theme_for_time(tiers: &[(f64, Option<(u32, u32)>)], time: f64) -> usize {
+    tiers
+        .iter()
+        .take_while(|&&(from, _)| time >= from)
+        .count()
+        .max(1) - 1
+}
+
+// Runs at some speeds start further along, on the first theme listing that speed
+fn start_time(tiers: &[(f64, Option<(u32, u32)>)], game_speed: u32) -> f64 {
+    tiers
+        .iter()
+        .find(|&&(_, speeds)| match speeds {
+            Some((min, max)) => game_speed >= min && game_speed <= max,
+            None => false,
+        })
+        .map_or(0.0, |&(from, _)| from)
+}
+
+struct Star {
+    pos: Point, // Within the tile
+    size: f64,
+    color: [f32; 4],
+    twinkle_rate: f64,
+    phase: f64,
+}
+
+impl Star {
+    // Dims between full brightness and the layer's twinkle amount
+    fn brightness(&self, twinkle: f64, time: f64) -> f32 {
+        use std::f64::consts::PI;
+
+        let wave = 0.5 + 0.5 * (time * self.twinkle_rate * 2.0 * PI + self.phase).sin();
+        (1.0 - twinkle * wave) as f32
+    }
+}
+
+struct Cloud {
+    pos: Point, // Within the tile
+    radius: f64,
+    color: [f32; 4],
+}
+
+enum Layer {
+    Image(Sprite<G2dTexture>),
+    Stars(Vec<Star>, f64), // (stars, twinkle)
+    Nebula(Vec<Cloud>),
+}
+
+fn generate_stars(config: &StarLayerConfig, rng: &mut SeededRng) -> Vec<Star> {
+    use settings::background::TILE_SIZE;
+
+    let count = (config.density * TILE_SIZE * TILE_SIZE / 1_000_000.0).round() as usize;
+    (0..count)
+        .map(|_| {
+            let color = config.colors[rng.index(config.colors.len())];
+            Star {
+                pos: Point::new(rng.between((0.0, TILE_SIZE)), rng.between((0.0, TILE_SIZE))),
+                size: rng.between(config.size),
+                color: [color[0], color[1], color[2], rng.between(config.opacity) as f32],
+                twinkle_rate: rng.between(config.twinkle_rate),
+                phase: rng.between((0.0, 2.0 * ::std::f64::consts::PI)),
+            }
+        })
+        .collect()
+}
+
+fn generate_nebula(config: &NebulaConfig, rng: &mut SeededRng) -> Vec<Cloud> {
+    use settings::background::TILE_SIZE;
+
+    (0..config.clouds)
+        .map(|_| Cloud {
+            pos: Point::new(rng.between((0.0, TILE_SIZE)), rng.between((0.0, TILE_SIZE))),
+            radius: rng.between(config.radius),
+            color: config.colors[rng.index(config.colors.len())],
+        })
+        .collect()
+}
+
 struct BGLayer {
-    sprite: Sprite<G2dTexture>,
-    pos: Point,
+    layer: Layer,
     clamp: Point,
     factor: f64,
 }
 
 impl BGLayer {
-    fn new(sprite: Sprite<G2dTexture>, factor: f64) -> BGLayer {
-        let clamp = sprite.bounding_box();
-        let clamp = Point::new(clamp[2], clamp[3]);
+    fn new(layer: Layer, clamp: Point, factor: f64) -> BGLayer {
         BGLayer {
-            sprite,
-            pos: Point::new(0.0, 0.0),
+            layer,
             clamp,
             factor,
         }
     }
 
-    fn update(&mut self, player: &Player, dt: f64) {
-        // Update position based off player movement
-        self.pos = self.pos - player.velocity() * dt * self.factor;
+    // Distant layers move less than the camera, tiling the layer across the screen
+    fn offset(&self, camera_pos: Point) -> Point {
+        let pos = camera_pos * -self.factor;
 
         // Clamp position to bounding box
-        let new_x = ((self.pos.x % self.clamp.x) + self.clamp.x) % self.clamp.x;
-        let new_y = ((self.pos.y % self.clamp.y) + self.clamp.y) % self.clamp.y;
-        self.pos = Point::new(new_x, new_y);
+        Point::new(
+            ((pos.x % self.clamp.x) + self.clamp.x) % self.clamp.x,
+            ((pos.y % self.clamp.y) + self.clamp.y) % self.clamp.y,
+        )
     }
 
+    // The tint's alpha fades the whole layer
     fn draw(
         &mut self,
-        height: u32,
-        width: u32,
+        camera_pos: Point,
+        time: f64,
+        tint: [f32; 4],
+        viewport: &Viewport,
         context: piston_window::Context,
         g: &mut G2d,
     ) -> () {
-        let max_x = ((width as f64) / (self.clamp.x)) as i32 + 1;
-        let max_y = ((height as f64) / (self.clamp.y)) as i32 + 1;
+        let pos = self.offset(camera_pos);
+        let max_x = (viewport.width() / self.clamp.x) as i32 + 1;
+        let max_y = (viewport.height() / self.clamp.y) as i32 + 1;
 
         for x in -1..=max_x {
             for y in -1..=max_y {
-                let x_pos = self.pos.x + (x as f64) * self.clamp.x;
-                let y_pos = self.pos.y + (y as f64) * self.clamp.y;
+                let x_pos = pos.x + (x as f64) * self.clamp.x;
+                let y_pos = pos.y + (y as f64) * self.clamp.y;
+
+                let tile_pos = Point::new(x_pos, y_pos);
+                self.draw_tile(tile_pos, time, tint, viewport, context, g);
+            }
+        }
+    }
 
-                self.sprite.set_position(x_pos, y_pos);
-                self.sprite.draw(context.transform, g);
+    fn draw_tile(
+        &mut self,
+        tile_pos: Point,
+        time: f64,
+        tint: [f32; 4],
+        viewport: &Viewport,
+        context: piston_window::Context,
+        g: &mut G2d,
+    ) -> () {
+        use settings::background::NEBULA_RINGS;
+
+        match self.layer {
+            Layer::Image(ref mut sprite) => {
+                sprite.set_position(tile_pos.x, tile_pos.y);
+                sprite.set_opacity(tint[3]);
+                sprite.draw(context.transform, g);
             }
+            Layer::Stars(ref stars, twinkle) => {
+                for star in stars.iter() {
+                    let pos = tile_pos + star.pos;
+                    if !is_visible(pos, star.size, viewport) {
+                        continue;
+                    }
+                    let color = shade(star.color, tint, star.brightness(twinkle, time));
+                    let rect = [
+                        pos.x - star.size / 2.0,
+                        pos.y - star.size / 2.0,
+                        star.size,
+                        star.size,
+                    ];
+                    ellipse(color, rect, context.transform, g);
+                }
+            }
+            Layer::Nebula(ref clouds) => {
+                for cloud in clouds.iter() {
+                    let pos = tile_pos + cloud.pos;
+                    if !is_visible(pos, cloud.radius * 2.0, viewport) {
+                        continue;
+                    }
+                    // Stacked rings build up to a soft centre
+                    let color = shade(cloud.color, tint, 1.0 / NEBULA_RINGS as f32);
+                    for ring in 0..NEBULA_RINGS {
+                        let radius = cloud.radius * (1.0 - ring as f64 / NEBULA_RINGS as f64);
+                        let rect = [pos.x - radius, pos.y - radius, radius * 2.0, radius * 2.0];
+                        ellipse(color, rect, context.transform, g);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn shade(color: [f32; 4], tint: [f32; 4], opacity: f32) -> [f32; 4] {
+    [
+        color[0] * tint[0],
+        color[1] * tint[1],
+        color[2] * tint[2],
+        color[3] * tint[3] * opacity,
+    ]
+}
+
+fn is_visible(pos: Point, size: f64, viewport: &Viewport) -> bool {
+    pos.x > -size && pos.y > -size && pos.x < viewport.width() + size
+        && pos.y < viewport.height() + size
+}
+
+// SplitMix64, so a seed gives the same sky on every machine and version
+struct SeededRng(u64);
+
+impl SeededRng {
+    fn new(seed: u64) -> SeededRng {
+        SeededRng(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1)
+    fn fraction(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn between(&mut self, (min, max): (f64, f64)) -> f64 {
+        min + (max - min) * self.fraction()
+    }
+
+    fn index(&mut self, len: usize) -> usize {
+        (self.next() % len as u64) as usize
+    }
+}
+
+// A new sky each run unless the config fixes the seed
+pub fn sky_seed(fixed: Option<u64>) -> u64 {
+    use rand::{thread_rng, Rng};
+
+    fixed.unwrap_or_else(|| thread_rng().gen::<u64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn star_positions(background: &Background) -> Vec<(f64, f64)> {
+        background
+            .layers
+            .iter()
+            .flat_map(|layer| match layer.layer {
+                Layer::Stars(ref stars, _) => stars.iter().map(|s| (s.pos.x, s.pos.y)).collect(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_should_repeat_sky_for_same_seed() {
+        let first = star_positions(&Background::procedural(42));
+        let second = star_positions(&Background::procedural(42));
+        let other = star_positions(&Background::procedural(43));
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn it_should_fill_layers_to_density() {
+        use settings::background::{STAR_LAYERS, TILE_SIZE};
+
+        let background = Background::procedural(7);
+        let expected: usize = STAR_LAYERS
+            .iter()
+            .map(|l| (l.density * TILE_SIZE * TILE_SIZE / 1_000_000.0).round() as usize)
+            .sum();
+        let positions = star_positions(&background);
+        assert_eq!(positions.len(), expected);
+        assert!(
+            positions
+                .iter()
+                .all(|&(x, y)| (0.0..TILE_SIZE).contains(&x) && (0.0..TILE_SIZE).contains(&y))
+        );
+    }
+
+    #[test]
+    fn it_should_twinkle_within_range() {
+        let star = Star {
+            pos: Point::new(0.0, 0.0),
+            size: 1.0,
+            color: [1.0; 4],
+            twinkle_rate: 1.0,
+            phase: 0.0,
+        };
+        for i in 0..20 {
+            let brightness = star.brightness(0.6, i as f64 * 0.05);
+            assert!((0.4 - 1e-6..=1.0 + 1e-6).contains(&brightness));
+        }
+        assert_approx_eq!(star.brightness(0.6, 0.25) as f64, 0.4);
+    }
+
+    #[test]
+    fn it_should_pick_theme_for_tier() {
+        let tiers = [(0.0, None), (60.0, None), (120.0, None)];
+        assert_eq!(theme_for_time(&tiers, 0.0), 0);
+        assert_eq!(theme_for_time(&tiers, 59.9), 0);
+        assert_eq!(theme_for_time(&tiers, 60.0), 1);
+        assert_eq!(theme_for_time(&tiers, 500.0), 2);
+    }
+
+    #[test]
+    fn it_should_start_on_theme_for_game_speed() {
+        let tiers = [(0.0, Some((80, 100))), (60.0, Some((50, 70))), (120.0, None)];
+        assert_eq!(start_time(&tiers, 100), 0.0);
+        assert_eq!(start_time(&tiers, 60), 60.0);
+        assert_eq!(start_time(&tiers, 75), 0.0);
+        assert_eq!(theme_for_time(&tiers, start_time(&tiers, 60) + 60.0), 2);
+    }
+
+    #[test]
+    fn it_should_parse_themes() {
+        let (themes, errors) = parse_themes(
+            "[calm]\nlayer = a.png, 0.0\nlayer = b.png, 0.5\nspeeds = 50-70\n\n\
+             [storm]\nlayer = a.png, 0.1\ntint = 1.0, 0.5, 0.5\nscroll = 2\nfrom = 30\n",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(themes.len(), 2);
+        assert_eq!(themes[0].layers, vec![("a.png".to_owned(), 0.0), ("b.png".to_owned(), 0.5)]);
+        assert_eq!(themes[0].speeds, Some((50, 70)));
+        assert_eq!(themes[1].tint, [1.0, 0.5, 0.5]);
+        assert_eq!(themes[1].scroll, 2.0);
+        assert_eq!(themes[1].from, 30.0);
+    }
+
+    #[test]
+    fn it_should_report_theme_errors() {
+        let (_, errors) = parse_themes(
+            "[a]\nlayer = a.png\nfrom = 10\n[b]\nlayer = b.png, 1\nspeeds = 9-1\nfog = 1\n",
+        );
+        assert_eq!(errors.len(), 6, "{:?}", errors);
+        assert_eq!(parse_themes("").1, vec!["No themes".to_owned()]);
+    }
+
+    #[test]
+    fn it_should_stay_in_unit_range() {
+        let mut rng = SeededRng::new(1);
+        for _ in 0..1000 {
+            let f = rng.fraction();
+            assert!((0.0..1.0).contains(&f));
         }
     }
 }
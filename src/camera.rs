@@ -0,0 +1,151 @@
+extern crate piston_window;
+
+use game::*;
+use piston_window::*;
+use viewport::Viewport;
+
+// Looks at a point in the world, which is drawn at the centre of the viewport
+pub struct Camera {
+    pos: Point,
+    zoom: f64,
+    trauma: f64, // 0 to 1, how hard the camera is shaking
+    shake: Point,
+    viewport: Viewport,
+}
+
+impl Camera {
+    pub fn new(pos: Point, viewport: Viewport) -> Camera {
+        use settings::camera::ZOOM;
+
+        Camera {
+            pos,
+            zoom: ZOOM,
+            trauma: 0.0,
+            shake: Point::new(0.0, 0.0),
+            viewport,
+        }
+    }
+
+    // Keeps the camera in step with the window after a resize
+    pub fn set_viewport(&mut self, viewport: Viewport) -> () {
+        self.viewport = viewport;
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    // Ease towards a point ahead of the target in the direction it is moving
+    pub fn update(&mut self, target: Point, velocity: Point, dt: f64) -> () {
+        use settings::camera::{LOOK_AHEAD, SMOOTHING};
+
+        let goal = target + velocity * LOOK_AHEAD;
+        let t = 1.0 - (-SMOOTHING * dt).exp();
+        self.pos = self.pos + (goal - self.pos) * t;
+    }
+
+    // Shake falls off with the square of trauma, so small knocks stay subtle
+    pub fn update_shake(&mut self, dt: f64) -> () {
+        use rand::{thread_rng, Rng};
+        use settings::screen_shake::{MAX_OFFSET, TRAUMA_DECAY};
+
+        self.trauma = (self.trauma - TRAUMA_DECAY * dt).max(0.0);
+
+        let mut rng = thread_rng();
+        let strength = MAX_OFFSET * self.trauma * self.trauma;
+        self.shake = Point::new(
+            rng.gen_range(-1.0, 1.0) * strength,
+            rng.gen_range(-1.0, 1.0) * strength,
+        );
+    }
+
+    pub fn add_trauma(&mut self, amount: f64) -> () {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    pub fn snap_to(&mut self, pos: Point) -> () {
+        self.pos = pos;
+        self.trauma = 0.0;
+        self.shake = Point::new(0.0, 0.0);
+    }
+
+    pub fn pos(&self) -> Point {
+        self.pos
+    }
+
+    // How far the view is currently knocked off centre
+    pub fn shake(&self) -> Point {
+        self.shake
+    }
+
+    // Turns a context for the logical screen into one for drawing in world coordinates
+    pub fn transform(&self, c: Context) -> Context {
+        let centre = self.viewport.centre() + self.shake;
+        let mut c = c;
+        c.transform = c.transform
+            .trans(centre.x, centre.y)
+            .zoom(self.zoom)
+            .trans(-self.pos.x, -self.pos.y);
+        c
+    }
+
+    // Where a point in the world is drawn, shake included, so pointers and mouse steering line
+    // up with what's on screen
+    pub fn to_screen(&self, pos: Point) -> Point {
+        (pos - self.pos) * self.zoom + self.viewport.centre() + self.shake
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_put_camera_position_at_screen_centre() {
+        let viewport = Viewport::new(1440.0, 720.0);
+        let camera = Camera::new(Point::new(300.0, -200.0), viewport);
+        let pos = camera.to_screen(Point::new(300.0, -200.0));
+        assert_approx_eq!(pos.x, viewport.centre().x);
+        assert_approx_eq!(pos.y, viewport.centre().y);
+    }
+
+    #[test]
+    fn it_should_shake_screen_positions_with_the_world() {
+        let viewport = Viewport::new(1440.0, 720.0);
+        let mut camera = Camera::new(Point::new(0.0, 0.0), viewport);
+        camera.shake = Point::new(3.0, -4.0);
+        let pos = camera.to_screen(Point::new(0.0, 0.0));
+        assert_approx_eq!(pos.x, viewport.centre().x + 3.0);
+        assert_approx_eq!(pos.y, viewport.centre().y - 4.0);
+    }
+
+    #[test]
+    fn it_should_settle_ahead_of_moving_target() {
+        use settings::camera::LOOK_AHEAD;
+
+        let mut camera = Camera::new(Point::new(0.0, 0.0), Viewport::new(1440.0, 720.0));
+        let target = Point::new(100.0, 0.0);
+        let velocity = Point::new(200.0, 0.0);
+        for _ in 0..600 {
+            camera.update(target, velocity, 1.0 / 60.0);
+        }
+        assert_approx_eq!(camera.pos().x, 100.0 + 200.0 * LOOK_AHEAD);
+        assert_approx_eq!(camera.pos().y, 0.0);
+    }
+
+    #[test]
+    fn it_should_cap_and_decay_trauma() {
+        use settings::screen_shake::{MAX_OFFSET, TRAUMA_DECAY};
+
+        let mut camera = Camera::new(Point::new(0.0, 0.0), Viewport::new(1440.0, 720.0));
+        camera.add_trauma(0.8);
+        camera.add_trauma(0.8);
+        assert_approx_eq!(camera.trauma, 1.0);
+
+        camera.update_shake(0.0);
+        assert!(camera.shake().magnitude() <= MAX_OFFSET * 2.0_f64.sqrt());
+
+        camera.update_shake(1.0 / TRAUMA_DECAY);
+        assert_approx_eq!(camera.shake().magnitude(), 0.0);
+    }
+}
@@ -0,0 +1,280 @@
+use controls::Bindings;
+use input::InputMode;
+use settings;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Settings the player can change from the options screen, stored as "name = value" lines
+pub struct Config {
+    path: PathBuf,
+    pub bindings: Bindings,
+    pub input_mode: InputMode,
+    pub hold_turns_right: bool,
+    pub game_speed: u32, // Percentage
+    pub screen_shake: bool,
+    pub hit_stop: bool,
+    pub pointer_labels: bool,
+    pub procedural_background: bool,
+    pub background_seed: Option<u64>, // None for a new sky every run
+    pub locale: String,
+}
+
+impl Config {
+    pub fn load(path: PathBuf) -> (Config, Vec<String>) {
+        // A missing file just means nothing has been changed yet
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        Config::parse(path, &contents)
+    }
+
+    // Falls back to defaults for anything that can't be read, returning the problems found
+    fn parse(path: PathBuf, contents: &str) -> (Config, Vec<String>) {
+        let mut config = Config {
+            path,
+            bindings: Bindings::new(),
+            input_mode: settings::input::DEFAULT_MODE,
+            hold_turns_right: settings::input::HOLD_TURNS_RIGHT,
+            game_speed: settings::game_speed::DEFAULT,
+            screen_shake: settings::screen_shake::ENABLED,
+            hit_stop: settings::screen_shake::HIT_STOP,
+            pointer_labels: settings::offscreen_pointer::SHOW_LABELS,
+            procedural_background: settings::background::PROCEDURAL,
+            background_seed: settings::background::SEED,
+            locale: settings::locale::DEFAULT.to_owned(),
+        };
+        let mut errors = vec![];
+
+        for (line_no, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.splitn(2, '=');
+            let (name, value) = match (fields.next(), fields.next()) {
+                (Some(name), Some(value)) => (name.trim(), value.trim()),
+                _ => {
+                    errors.push(format!("Line {}: expected 'name = value'", line_no + 1));
+                    continue;
+                }
+            };
+
+            if let Err(err) = config.set(name, value) {
+                errors.push(format!("Line {}: {}", line_no + 1, err));
+            }
+        }
+
+        if let Err(err) = config.bindings.validate() {
+            errors.push(format!("{}, using default controls", err));
+            config.bindings = Bindings::new();
+        }
+
+        (config, errors)
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "input_mode" => {
+                self.input_mode = InputMode::from_config_name(value)
+                    .ok_or_else(|| format!("Unknown input mode '{}'", value))?;
+            }
+            "hold_direction" => {
+                self.hold_turns_right = match value {
+                    "left" => false,
+                    "right" => true,
+                    _ => return Err(format!("Expected 'left' or 'right', found '{}'", value)),
+                };
+            }
+            "game_speed" => {
+                use settings::game_speed::{MAX, MIN, STEP};
+
+                // Only the speeds the options screen offers, as each one keeps its own high scores
+                let mut speeds = (MIN..=MAX).step_by(STEP as usize);
+                self.game_speed = match value.parse::<u32>() {
+                    Ok(speed) if speeds.any(|s| s == speed) => speed,
+                    _ => {
+                        return Err(format!(
+                            "Game speed must be {} to {} in steps of {}",
+                            MIN, MAX, STEP
+                        ))
+                    }
+                };
+            }
+            "screen_shake" => self.screen_shake = parse_switch(value)?,
+            "hit_stop" => self.hit_stop = parse_switch(value)?,
+            "pointer_labels" => self.pointer_labels = parse_switch(value)?,
+            "procedural_background" => self.procedural_background = parse_switch(value)?,
+            "background_seed" => {
+                self.background_seed = match value {
+                    "random" => None,
+                    _ => Some(value.parse::<u64>().map_err(|_| {
+                        format!("Background seed must be 'random' or a number, found '{}'", value)
+                    })?),
+                };
+            }
+            "locale" => {
+                if value.is_empty() || !value.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(format!("Unknown locale '{}'", value));
+                }
+                self.locale = value.to_owned();
+            }
+            _ => self.bindings
+                .parse_line(name, value)
+                .map_err(|err| err.to_string())?,
+        }
+        Ok(())
+    }
+
+    // Scale for simulation time
+    pub fn time_scale(&self) -> f64 {
+        self.game_speed as f64 / 100.0
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        use controls::key_name;
+
+        let mut contents = String::from("# Iron Sky configuration\n");
+        contents += &format!("input_mode = {}\n", self.input_mode.config_name());
+        contents += &format!(
+            "hold_direction = {}\n",
+            if self.hold_turns_right { "right" } else { "left" }
+        );
+        contents += &format!("game_speed = {}\n", self.game_speed);
+        contents += &format!("screen_shake = {}\n", switch_name(self.screen_shake));
+        contents += &format!("hit_stop = {}\n", switch_name(self.hit_stop));
+        contents += &format!("pointer_labels = {}\n", switch_name(self.pointer_labels));
+        contents += &format!(
+            "procedural_background = {}\n",
+            switch_name(self.procedural_background)
+        );
+        contents += &match self.background_seed {
+            Some(seed) => format!("background_seed = {}\n", seed),
+            None => "background_seed = random\n".to_owned(),
+        };
+        contents += &format!("locale = {}\n", self.locale);
+        for &(action, key) in self.bindings.all().iter() {
+            contents += &format!("{} = {}\n", action.config_name(), key_name(key));
+        }
+
+        fs::write(&self.path, contents)
+    }
+}
+
+fn parse_switch(value: &str) -> Result<bool, String> {
+    match value {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("Expected 'on' or 'off', found '{}'", value)),
+    }
+}
+
+pub fn switch_name(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use controls::GameAction;
+    use piston_window::Key;
+
+    #[test]
+    fn it_should_read_bindings() {
+        let (config, errors) = Config::parse(
+            PathBuf::new(),
+            "# Comment\nturn_left = A\n\nturn_right = D\n",
+        );
+        assert!(errors.is_empty());
+        assert_eq!(config.bindings.key(GameAction::TurnLeft), Key::A);
+        assert_eq!(config.bindings.key(GameAction::TurnRight), Key::D);
+    }
+
+    #[test]
+    fn it_should_report_bad_lines() {
+        let (_, errors) = Config::parse(
+            PathBuf::new(),
+            "turn_left = NotAKey\nfly = A\nboost\ninput_mode = telepathy\ngame_speed = 20\n",
+        );
+        assert_eq!(errors.len(), 5);
+    }
+
+    #[test]
+    fn it_should_only_accept_game_speeds_on_the_options_steps() {
+        let (config, errors) = Config::parse(PathBuf::new(), "game_speed = 70
+");
+        assert!(errors.is_empty());
+        assert_eq!(config.game_speed, 70);
+
+        let (config, errors) = Config::parse(PathBuf::new(), "game_speed = 73
+");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(config.game_speed, settings::game_speed::DEFAULT);
+    }
+
+    #[test]
+    fn it_should_read_input_mode() {
+        let (config, errors) = Config::parse(PathBuf::new(), "input_mode = one_button_hold\n");
+        assert!(errors.is_empty());
+        assert_eq!(config.input_mode, InputMode::OneButtonHold);
+    }
+
+    #[test]
+    fn it_should_read_hold_direction() {
+        let (config, errors) = Config::parse(PathBuf::new(), "hold_direction = left\n");
+        assert!(errors.is_empty());
+        assert!(!config.hold_turns_right);
+
+        let (config, errors) = Config::parse(PathBuf::new(), "hold_direction = up\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(config.hold_turns_right, settings::input::HOLD_TURNS_RIGHT);
+    }
+
+    #[test]
+    fn it_should_read_effect_switches() {
+        let (config, errors) =
+            Config::parse(PathBuf::new(), "screen_shake = off\nhit_stop = on\n");
+        assert!(errors.is_empty());
+        assert!(!config.screen_shake);
+        assert!(config.hit_stop);
+
+        let (_, errors) = Config::parse(PathBuf::new(), "screen_shake = maybe\n");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn it_should_read_background_seed() {
+        let (config, errors) = Config::parse(PathBuf::new(), "background_seed = 1234\n");
+        assert!(errors.is_empty());
+        assert_eq!(config.background_seed, Some(1234));
+
+        let (config, errors) = Config::parse(PathBuf::new(), "background_seed = random\n");
+        assert!(errors.is_empty());
+        assert_eq!(config.background_seed, None);
+
+        let (_, errors) = Config::parse(PathBuf::new(), "background_seed = -5\n");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn it_should_read_locale() {
+        let (config, errors) = Config::parse(PathBuf::new(), "locale = de\n");
+        assert!(errors.is_empty());
+        assert_eq!(config.locale, "de");
+
+        let (config, errors) = Config::parse(PathBuf::new(), "locale = ../de\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(config.locale, settings::locale::DEFAULT);
+    }
+
+    #[test]
+    fn it_should_fall_back_to_defaults_on_conflict() {
+        let (config, errors) =
+            Config::parse(PathBuf::new(), "turn_left = A\nturn_right = A\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(config.bindings, Bindings::new());
+    }
+}
@@ -0,0 +1,252 @@
+extern crate piston_window;
+
+use piston_window::*;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GameAction {
+    TurnLeft,
+    TurnRight,
+    Restart,
+    ChangeShip,
+    Pause,
+    Boost,
+    Debug,
+    Options,
+    Quit,
+}
+
+pub const ACTIONS: [GameAction; 9] = [
+    GameAction::TurnLeft,
+    GameAction::TurnRight,
+    GameAction::Restart,
+    GameAction::ChangeShip,
+    GameAction::Pause,
+    GameAction::Boost,
+    GameAction::Debug,
+    GameAction::Options,
+    GameAction::Quit,
+];
+
+impl GameAction {
+    // Name used in the config file
+    pub fn config_name(&self) -> &'static str {
+        match *self {
+            GameAction::TurnLeft => "turn_left",
+            GameAction::TurnRight => "turn_right",
+            GameAction::Restart => "restart",
+            GameAction::ChangeShip => "change_ship",
+            GameAction::Pause => "pause",
+            GameAction::Boost => "boost",
+            GameAction::Debug => "debug",
+            GameAction::Options => "options",
+            GameAction::Quit => "quit",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<GameAction> {
+        ACTIONS.iter().find(|a| a.config_name() == name).cloned()
+    }
+
+    pub fn label(&self) -> &'static str {
+        match *self {
+            GameAction::TurnLeft => "Turn Left",
+            GameAction::TurnRight => "Turn Right",
+            GameAction::Restart => "Restart",
+            GameAction::ChangeShip => "Change Ship",
+            GameAction::Pause => "Pause",
+            GameAction::Boost => "Boost",
+            GameAction::Debug => "Debug",
+            GameAction::Options => "Options",
+            GameAction::Quit => "Quit",
+        }
+    }
+}
+
+const BINDABLE_KEYS: [Key; 60] = [
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J,
+    Key::K, Key::L, Key::M, Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T,
+    Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z, Key::D0, Key::D1, Key::D2, Key::D3,
+    Key::D4, Key::D5, Key::D6, Key::D7, Key::D8, Key::D9, Key::Left, Key::Right, Key::Up,
+    Key::Down, Key::Space, Key::Return, Key::Backspace, Key::LShift, Key::RShift,
+    Key::LCtrl, Key::RCtrl, Key::LAlt, Key::RAlt, Key::F1, Key::F2, Key::F3, Key::F4,
+    Key::Minus, Key::Equals, Key::Comma, Key::Period, Key::Slash, Key::Escape, Key::Tab,
+];
+
+pub fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
+
+// How a key is shown to the player, the same in every prompt and menu
+pub fn key_label(key: Key) -> String {
+    key_name(key).to_uppercase()
+}
+
+pub fn parse_key(name: &str) -> Option<Key> {
+    BINDABLE_KEYS.iter().find(|&&k| key_name(k) == name).cloned()
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BindingError {
+    UnknownAction(String),
+    UnknownKey(String),
+    Conflict(GameAction, GameAction, Key),
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BindingError::UnknownAction(ref name) => write!(f, "Unknown action '{}'", name),
+            BindingError::UnknownKey(ref name) => write!(f, "Unknown key '{}'", name),
+            BindingError::Conflict(a, b, key) => write!(
+                f,
+                "{} is bound to both {} and {}",
+                key_name(key),
+                a.label(),
+                b.label()
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+    keys: Vec<(GameAction, Key)>,
+}
+
+impl Bindings {
+    pub fn new() -> Bindings {
+        use settings::controls::*;
+
+        Bindings {
+            keys: vec![
+                (GameAction::TurnLeft, TURN_LEFT),
+                (GameAction::TurnRight, TURN_RIGHT),
+                (GameAction::Restart, RESTART),
+                (GameAction::ChangeShip, CHANGE_SHIP),
+                (GameAction::Pause, PAUSE),
+                (GameAction::Boost, BOOST),
+                (GameAction::Debug, DEBUG),
+                (GameAction::Options, OPTIONS),
+                (GameAction::Quit, QUIT),
+            ],
+        }
+    }
+
+    pub fn key(&self, action: GameAction) -> Key {
+        self.keys
+            .iter()
+            .find(|&&(a, _)| a == action)
+            .map(|&(_, k)| k)
+            .unwrap()
+    }
+
+    pub fn action(&self, button: Button) -> Option<GameAction> {
+        match button {
+            Button::Keyboard(key) => self.keys
+                .iter()
+                .find(|&&(_, k)| k == key)
+                .map(|&(a, _)| a),
+            _ => None,
+        }
+    }
+
+    // Binds the key only if it leaves the bindings free of conflicts
+    pub fn bind(&mut self, action: GameAction, key: Key) -> Result<(), BindingError> {
+        let mut new_bindings = self.clone();
+        new_bindings.set(action, key);
+        new_bindings.validate()?;
+        *self = new_bindings;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), BindingError> {
+        for (i, &(action, key)) in self.keys.iter().enumerate() {
+            if let Some(&(other, _)) = self.keys[(i + 1)..].iter().find(|&&(_, k)| k == key) {
+                return Err(BindingError::Conflict(action, other, key));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn all(&self) -> &[(GameAction, Key)] {
+        &self.keys
+    }
+
+    fn set(&mut self, action: GameAction, key: Key) -> () {
+        for binding in self.keys.iter_mut() {
+            if binding.0 == action {
+                binding.1 = key;
+            }
+        }
+    }
+
+    pub fn parse_line(&mut self, name: &str, value: &str) -> Result<(), BindingError> {
+        let action = GameAction::from_config_name(name)
+            .ok_or_else(|| BindingError::UnknownAction(name.to_owned()))?;
+        let key = parse_key(value).ok_or_else(|| BindingError::UnknownKey(value.to_owned()))?;
+        self.set(action, key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings_should_be_valid() {
+        assert_eq!(Bindings::new().validate(), Ok(()));
+    }
+
+    #[test]
+    fn it_should_reject_conflicting_binding() {
+        let mut bindings = Bindings::new();
+        let left = bindings.key(GameAction::TurnLeft);
+
+        assert_eq!(
+            bindings.bind(GameAction::Boost, left),
+            Err(BindingError::Conflict(
+                GameAction::TurnLeft,
+                GameAction::Boost,
+                left
+            ))
+        );
+        assert_eq!(bindings, Bindings::new());
+    }
+
+    #[test]
+    fn it_should_rebind_menu_keys() {
+        let mut bindings = Bindings::new();
+        bindings.bind(GameAction::Quit, Key::Q).unwrap();
+        bindings.bind(GameAction::Pause, Key::Escape).unwrap();
+
+        assert_eq!(
+            bindings.action(Button::Keyboard(Key::Q)),
+            Some(GameAction::Quit)
+        );
+        assert_eq!(
+            bindings.action(Button::Keyboard(Key::Escape)),
+            Some(GameAction::Pause)
+        );
+    }
+
+    #[test]
+    fn it_should_look_up_rebound_action() {
+        let mut bindings = Bindings::new();
+        bindings.bind(GameAction::TurnLeft, Key::A).unwrap();
+
+        assert_eq!(
+            bindings.action(Button::Keyboard(Key::A)),
+            Some(GameAction::TurnLeft)
+        );
+        assert_eq!(bindings.action(Button::Keyboard(Key::Left)), None);
+    }
+
+    #[test]
+    fn it_should_round_trip_key_names() {
+        for &key in BINDABLE_KEYS.iter() {
+            assert_eq!(parse_key(&key_name(key)), Some(key));
+        }
+    }
+}
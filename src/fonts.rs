@@ -0,0 +1,256 @@
+extern crate piston_window;
+
+use piston_window::*;
+use std::fs;
+use std::path::Path;
+
+type Coverage = Vec<(u32, u32)>; // Ranges of characters a font has glyphs for
+
+// A list of fonts tried in order for each character, so text in scripts the main font lacks
+// still shows up
+pub struct Fonts {
+    fonts: Vec<(Glyphs, Coverage)>,
+}
+
+impl Fonts {
+    // The first file is the main font and must load. Fallbacks that can't be read are skipped,
+    // and returned as errors alongside the fonts that did load.
+    pub fn load(
+        window: &mut PistonWindow,
+        folder: &Path,
+        files: &[&str],
+    ) -> Result<(Fonts, Vec<String>), String> {
+        let mut fonts = vec![];
+        let mut errors = vec![];
+
+        for (i, file) in files.iter().enumerate() {
+            let path = folder.join(file);
+            let coverage = fs::read(&path).map(|bytes| coverage(&bytes));
+            let glyphs = Glyphs::new(&path, window.factory.clone(), TextureSettings::new());
+            match (glyphs, coverage) {
+                (Ok(glyphs), Ok(coverage)) => fonts.push((glyphs, coverage)),
+                _ if i == 0 => return Err(format!("Could not load font {}", file)),
+                _ => errors.push(format!("Could not load fallback font {}", file)),
+            }
+        }
+
+        Ok((Fonts { fonts }, errors))
+    }
+
+    pub fn width(&mut self, font_size: u32, text: &str) -> f64 {
+        let runs = self.runs(text);
+        runs.iter()
+            .map(|&(font, run)| self.fonts[font].0.width(font_size, run).unwrap())
+            .sum()
+    }
+
+    pub fn draw(
+        &mut self,
+        text: &str,
+        color: [f32; 4],
+        font_size: u32,
+        transform: [[f64; 3]; 2],
+        c: piston_window::Context,
+        g: &mut G2d,
+    ) -> () {
+        let mut h_offset = 0.0;
+        for (font, run) in self.runs(text) {
+            let glyphs = &mut self.fonts[font].0;
+            text::Text::new_color(color, font_size)
+                .round()
+                .draw(run, glyphs, &c.draw_state, transform.trans(h_offset, 0.0), g)
+                .unwrap();
+            h_offset += glyphs.width(font_size, run).unwrap();
+        }
+    }
+
+    fn runs<'a>(&self, text: &'a str) -> Vec<(usize, &'a str)> {
+        let coverage: Vec<&Coverage> = self.fonts.iter().map(|(_, c)| c).collect();
+        split_runs(text, &coverage)
+    }
+}
+
+// Splits text into runs that can each be drawn with one font, picking the first font in the
+// chain that has each character. Characters no font has are left to the main font.
+fn split_runs<'a>(text: &'a str, fonts: &[&Coverage]) -> Vec<(usize, &'a str)> {
+    let mut runs = vec![];
+    let mut start = 0;
+    let mut current = None;
+    for (idx, ch) in text.char_indices() {
+        // Spaces carry on in whichever font came before them
+        let font = match current {
+            Some(prev) if ch.is_whitespace() => prev,
+            _ => fonts
+                .iter()
+                .position(|ranges| covers(ranges, ch))
+                .unwrap_or(0),
+        };
+        match current {
+            Some(prev) if prev != font => {
+                runs.push((prev, &text[start..idx]));
+                start = idx;
+            }
+            _ => {}
+        }
+        current = Some(font);
+    }
+    if let Some(font) = current {
+        runs.push((font, &text[start..]));
+    }
+    runs
+}
+
+fn covers(ranges: &Coverage, ch: char) -> bool {
+    let ch = ch as u32;
+    ranges.iter().any(|&(first, last)| first <= ch && ch <= last)
+}
+
+// Reads which characters a TrueType or OpenType font maps from its cmap table. Only the common
+// Unicode subtables are understood, anything else counts as covering nothing.
+fn coverage(bytes: &[u8]) -> Coverage {
+    read_cmap(bytes).unwrap_or_default()
+}
+
+fn read_cmap(bytes: &[u8]) -> Option<Coverage> {
+    let num_tables = read_u16(bytes, 4)? as usize;
+    let cmap = (0..num_tables)
+        .map(|i| 12 + i * 16)
+        .find(|&record| bytes.get(record..record + 4) == Some(b"cmap"))
+        .and_then(|record| read_u32(bytes, record + 8))? as usize;
+
+    // Prefer the full Unicode subtables over the Basic Multilingual Plane only ones
+    let num_subtables = read_u16(bytes, cmap + 2)? as usize;
+    let mut best: Option<(u16, usize)> = None;
+    for i in 0..num_subtables {
+        let record = cmap + 4 + i * 8;
+        let platform = read_u16(bytes, record)?;
+        let encoding = read_u16(bytes, record + 2)?;
+        let subtable = cmap + read_u32(bytes, record + 4)? as usize;
+        let format = read_u16(bytes, subtable)?;
+        let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+        if unicode && (format == 4 || format == 12) && best.is_none_or(|(f, _)| format > f) {
+            best = Some((format, subtable));
+        }
+    }
+
+    match best? {
+        (4, subtable) => read_format_4(bytes, subtable),
+        (_, subtable) => read_format_12(bytes, subtable),
+    }
+}
+
+// Segments of 16 bit characters, ending with a 0xFFFF marker
+fn read_format_4(bytes: &[u8], subtable: usize) -> Option<Coverage> {
+    let segments = read_u16(bytes, subtable + 6)? as usize / 2;
+    let ends = subtable + 14;
+    let starts = ends + segments * 2 + 2;
+
+    let mut ranges = vec![];
+    for i in 0..segments {
+        let first = read_u16(bytes, starts + i * 2)? as u32;
+        let last = read_u16(bytes, ends + i * 2)? as u32;
+        if first != 0xFFFF {
+            ranges.push((first, last));
+        }
+    }
+    Some(ranges)
+}
+
+// Groups of 32 bit characters
+fn read_format_12(bytes: &[u8], subtable: usize) -> Option<Coverage> {
+    let groups = read_u32(bytes, subtable + 12)? as usize;
+    (0..groups)
+        .map(|i| {
+            let group = subtable + 16 + i * 12;
+            Some((read_u32(bytes, group)?, read_u32(bytes, group + 4)?))
+        })
+        .collect()
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let b = bytes.get(offset..offset + 2)?;
+    Some((b[0] as u16) << 8 | b[1] as u16)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some((read_u16(bytes, offset)? as u32) << 16 | read_u16(bytes, offset + 2)? as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_u16(bytes: &mut Vec<u8>, value: u16) -> () {
+        bytes.extend_from_slice(&[(value >> 8) as u8, value as u8]);
+    }
+
+    fn push_u32(bytes: &mut Vec<u8>, value: u32) -> () {
+        push_u16(bytes, (value >> 16) as u16);
+        push_u16(bytes, value as u16);
+    }
+
+    // A font with nothing but a cmap table, mapping the given format 4 segments
+    fn font_with_segments(segments: &[(u16, u16)]) -> Vec<u8> {
+        let mut bytes = vec![];
+        push_u32(&mut bytes, 0x0001_0000);
+        push_u16(&mut bytes, 1); // One table
+        bytes.extend_from_slice(&[0; 6]);
+        bytes.extend_from_slice(b"cmap");
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 28); // Straight after this record
+        push_u32(&mut bytes, 0);
+
+        // cmap header with one Windows Unicode subtable
+        push_u16(&mut bytes, 0);
+        push_u16(&mut bytes, 1);
+        push_u16(&mut bytes, 3);
+        push_u16(&mut bytes, 1);
+        push_u32(&mut bytes, 12);
+
+        let count = segments.len() as u16 + 1;
+        push_u16(&mut bytes, 4);
+        bytes.extend_from_slice(&[0; 4]);
+        push_u16(&mut bytes, count * 2);
+        bytes.extend_from_slice(&[0; 6]);
+        for &(_, last) in segments.iter() {
+            push_u16(&mut bytes, last);
+        }
+        push_u16(&mut bytes, 0xFFFF);
+        push_u16(&mut bytes, 0);
+        for &(first, _) in segments.iter() {
+            push_u16(&mut bytes, first);
+        }
+        push_u16(&mut bytes, 0xFFFF);
+        bytes
+    }
+
+    #[test]
+    fn it_should_read_format_4_coverage() {
+        let bytes = font_with_segments(&[(0x20, 0x7E), (0xAC00, 0xD7A3)]);
+        assert_eq!(coverage(&bytes), vec![(0x20, 0x7E), (0xAC00, 0xD7A3)]);
+    }
+
+    #[test]
+    fn it_should_cover_nothing_for_unreadable_fonts() {
+        assert!(coverage(b"").is_empty());
+        assert!(coverage(b"\x00\x01\x00\x00\x00\x05").is_empty());
+    }
+
+    #[test]
+    fn it_should_split_runs_by_font() {
+        let main = vec![(0x20, 0x7E)];
+        let fallback = vec![(0x20, 0xFF)];
+        let runs = split_runs("Größe 3", &[&main, &fallback]);
+        assert_eq!(runs, vec![(0, "Gr"), (1, "öß"), (0, "e 3")]);
+
+        let runs = split_runs("für ö", &[&main, &fallback]);
+        assert_eq!(runs, vec![(0, "f"), (1, "ü"), (0, "r "), (1, "ö")]);
+    }
+
+    #[test]
+    fn it_should_leave_missing_characters_to_main_font() {
+        let main = vec![(0x41, 0x5A)];
+        assert_eq!(split_runs("A→B", &[&main]), vec![(0, "A→B")]);
+        assert!(split_runs("", &[&main]).is_empty());
+    }
+}
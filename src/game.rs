@@ -1,22 +1,30 @@
 extern crate piston_window;
 
+use assets::placeholder_texture;
 use missile::Missile;
 use pickups::Pickup;
 use piston_window::*;
 use player::Player;
-use sprite::*;
+use spatial::colliding_pairs;
 use std::ops::{Add, Div, Mul, Sub};
 use traits::Collides;
 
 pub type Score = u32;
 
 #[derive(Copy, Clone, PartialEq)]
+pub enum Screen {
+    ShipSelect,
+    Options,
+    Playing,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum KeyState {
     Pressed,
     NotPressed,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
@@ -86,6 +94,10 @@ impl Point {
     pub fn magnitude(&self) -> f64 {
         (self.x.powi(2) + self.y.powi(2)).sqrt()
     }
+
+    pub fn dot(&self, other: Point) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -94,205 +106,386 @@ enum ColliderState {
     Disabled,
 }
 
+// Outline a collider is tested with. Shapes are given for a sprite facing right, centred on the
+// collider's position, and turn with the collider's rotation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Shape {
+    Circle(f64), // Radius
+    // A line along the sprite's heading, rounded off by the radius all the way round
+    Capsule { half_length: f64, r: f64 },
+    // Points in order round a convex outline
+    Polygon(&'static [Point]),
+}
+
+impl Shape {
+    // Radius of the smallest circle round the centre that holds the whole shape
+    pub fn radius(&self) -> f64 {
+        match *self {
+            Shape::Circle(r) => r,
+            Shape::Capsule { half_length, r } => half_length + r,
+            Shape::Polygon(points) => points.iter().map(|p| p.magnitude()).fold(0.0, f64::max),
+        }
+    }
+
+    // The shape as a convex core of one, two or more points, and how far it's rounded out past
+    // that core
+    fn core(&self, pos: Point, rot: f64) -> (Vec<Point>, f64) {
+        let (sin, cos) = rot.to_radians().sin_cos();
+        let place = |p: Point| pos + Point::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos);
+        match *self {
+            Shape::Circle(r) => (vec![pos], r),
+            Shape::Capsule { half_length, r } => {
+                let ends = vec![
+                    place(Point::new(-half_length, 0.0)),
+                    place(Point::new(half_length, 0.0)),
+                ];
+                (ends, r)
+            }
+            Shape::Polygon(points) => (points.iter().map(|&p| place(p)).collect(), 0.0),
+        }
+    }
+
+    fn check(&self) -> () {
+        match *self {
+            Shape::Circle(r) | Shape::Capsule { r, .. } if r <= 0.0 => {
+                panic!("Radius of collider must be greater than 0")
+            }
+            Shape::Capsule { half_length, .. } if half_length < 0.0 => {
+                panic!("Length of capsule collider can't be negative")
+            }
+            Shape::Polygon(points) if points.len() < 3 => {
+                panic!("Polygon collider needs at least 3 points")
+            }
+            _ => {}
+        }
+    }
+}
+
 pub struct Collider {
     state: ColliderState,
     pub pos: Point,
-    r: f64,
+    prev_pos: Point, // Where the collider was before its last move
+    rot: f64,        // Degrees
+    shape: Shape,
 }
 
 impl Collider {
     pub fn new(pos: Point, r: f64) -> Collider {
-        if r <= 0.0 {
-            panic!("Radius of collider must be greater than 0");
-        }
+        Collider::with_shape(pos, Shape::Circle(r))
+    }
+
+    pub fn with_shape(pos: Point, shape: Shape) -> Collider {
+        shape.check();
         Collider {
             state: ColliderState::Enabled,
             pos,
-            r,
+            prev_pos: pos,
+            rot: 0.0,
+            shape,
         }
     }
 
     pub fn collides_with(&self, other: &Collider) -> bool {
+        match (self.state, other.state) {
+            (ColliderState::Enabled, ColliderState::Enabled) => self.gap(other) < 0.0,
+            (ColliderState::Disabled, _) => false,
+            (_, ColliderState::Disabled) => false,
+        }
+    }
+
+    // Whether the two touched at any point while moving from their previous positions to their
+    // current ones, so fast movers can't pass through each other between frames
+    pub fn sweeps(&self, other: &Collider) -> bool {
         match (self.state, other.state) {
             (ColliderState::Enabled, ColliderState::Enabled) => {
-                let min_distance = self.r + other.r;
-                let distance = (self.pos - other.pos).magnitude();
-                distance < min_distance
+                self.time_of_impact(other).is_some()
             }
             (ColliderState::Disabled, _) => false,
             (_, ColliderState::Disabled) => false,
         }
     }
 
-    pub fn draw_debug(&self, c: piston_window::Context, g: &mut G2d) -> () {
-        match self.state {
-            ColliderState::Enabled => {
-                let rect = [
-                    self.pos.x - self.r,
-                    self.pos.y - self.r,
-                    self.r * 2.0,
-                    self.r * 2.0,
-                ];
-                ellipse([1.0, 0.0, 0.0, 0.5], rect, c.transform, g);
+    // How far through the last move the two first touched, from 0 to 1. Both keep their current
+    // rotation along the way.
+    pub fn time_of_impact(&self, other: &Collider) -> Option<f64> {
+        use settings::collision::{SWEEP_STEPS, SWEEP_TOLERANCE};
+
+        // Nothing can touch unless the circles round the two shapes do
+        let t = self.circle_time_of_impact(other)?;
+        if let (Shape::Circle(_), Shape::Circle(_)) = (self.shape, other.shape) {
+            return Some(t);
+        }
+
+        // Worked out in the frame of the other collider, with this one sliding towards it from
+        // where it started. Each step moves on by the gap between the shapes, which can't carry
+        // them past each other.
+        let (core, r) = self.core();
+        let (other_core, other_r) = other.core();
+        let start = (self.prev_pos - self.pos) - (other.prev_pos - other.pos);
+        let gap_at = |t: f64| {
+            let moved: Vec<Point> = core.iter().map(|&p| p + start * (1.0 - t)).collect();
+            core_distance(&moved, &other_core) - r - other_r
+        };
+
+        let mut t = t;
+        for _ in 0..SWEEP_STEPS {
+            let gap = gap_at(t);
+            if gap < SWEEP_TOLERANCE {
+                return Some(t);
+            }
+            t += gap / start.magnitude();
+            if t > 1.0 {
+                return None;
             }
-            ColliderState::Disabled => {}
+        }
+
+        // Still creeping closer after every step, so settle for where they ended up
+        if gap_at(1.0) < 0.0 {
+            Some(1.0)
+        } else {
+            None
         }
     }
 
-    pub fn enable(&mut self) -> () {
-        self.state = ColliderState::Enabled;
+    // Time of impact for the circles round both shapes, where this one travels in a straight
+    // line past the other standing still
+    fn circle_time_of_impact(&self, other: &Collider) -> Option<f64> {
+        let start = self.prev_pos - other.prev_pos;
+        let travel = (self.pos - other.pos) - start;
+        let min_distance = self.shape.radius() + other.shape.radius();
+
+        // Solve |start + travel * t| = min_distance for the earliest t
+        let a = travel.dot(travel);
+        let b = start.dot(travel);
+        let c = start.dot(start) - min_distance.powi(2);
+        if c < 0.0 {
+            return Some(0.0);
+        }
+        if a == 0.0 {
+            return None;
+        }
+        let discriminant = b * b - a * c;
+        if discriminant <= 0.0 {
+            return None;
+        }
+        let t = (-b - discriminant.sqrt()) / a;
+        if (0.0..=1.0).contains(&t) {
+            Some(t)
+        } else {
+            None
+        }
     }
 
-    pub fn disable(&mut self) -> () {
-        self.state = ColliderState::Disabled;
+    // Moves the collider along a path it can be swept along
+    pub fn move_to(&mut self, pos: Point) -> () {
+        self.prev_pos = self.pos;
+        self.pos = pos;
     }
-}
 
-pub struct AnimTexture {
-    texture: G2dTexture,
-    frames: Vec<[f64; 4]>,
-}
+    // Puts the collider somewhere without sweeping the space in between
+    pub fn place(&mut self, pos: Point) -> () {
+        self.prev_pos = pos;
+        self.pos = pos;
+    }
 
-impl AnimTexture {
-    pub fn new(
-        window: &mut PistonWindow,
-        folder: &::std::path::PathBuf,
-        file: &str,
-        rows: u32,
-        cols: u32,
-    ) -> AnimTexture {
-        let mut frames = vec![];
-        let texture = load_texture(window, folder, file);
-        let (width, height) = texture.get_size();
-        let (tex_width, tex_height) = (width / cols, height / rows);
-
-        for y in 0..rows {
-            for x in 0..cols {
-                frames.push([
-                    (x * tex_width) as f64,
-                    (y * tex_height) as f64,
-                    tex_width as f64,
-                    tex_height as f64,
-                ]);
-            }
+    pub fn set_rotation(&mut self, rot: f64) -> () {
+        self.rot = rot;
+    }
+
+    // Space between the edges of two colliders, negative when they overlap
+    pub fn gap(&self, other: &Collider) -> f64 {
+        let (core, r) = self.core();
+        let (other_core, other_r) = other.core();
+        core_distance(&core, &other_core) - r - other_r
+    }
+
+    pub fn is_near(&self, other: &Collider, margin: f64) -> bool {
+        match (self.state, other.state) {
+            (ColliderState::Enabled, ColliderState::Enabled) => self.gap(other) < margin,
+            (ColliderState::Disabled, _) => false,
+            (_, ColliderState::Disabled) => false,
         }
+    }
 
-        AnimTexture {
-            texture,
-            frames,
+    // Box around everywhere the collider passed through in its last move, as
+    // [min x, min y, max x, max y], or nothing if it can't collide
+    pub fn bounds(&self) -> Option<[f64; 4]> {
+        match self.state {
+            ColliderState::Enabled => {
+                let (core, r) = self.core();
+                let shift = self.prev_pos - self.pos;
+                let (low, high) = (f64::INFINITY, f64::NEG_INFINITY);
+                let mut bounds = [low, low, high, high];
+                for &p in core.iter() {
+                    for &q in [p, p + shift].iter() {
+                        bounds[0] = bounds[0].min(q.x - r);
+                        bounds[1] = bounds[1].min(q.y - r);
+                        bounds[2] = bounds[2].max(q.x + r);
+                        bounds[3] = bounds[3].max(q.y + r);
+                    }
+                }
+                Some(bounds)
+            }
+            ColliderState::Disabled => None,
         }
     }
-}
 
-pub struct Animation {
-    pos: Point,
-    length: f64,
-    duration: f64,
-    playing: bool,
-    scale: f64,
-}
+    pub fn set_shape(&mut self, shape: Shape) -> () {
+        shape.check();
+        self.shape = shape;
+    }
+
+    fn core(&self) -> (Vec<Point>, f64) {
+        self.shape.core(self.pos, self.rot)
+    }
 
-impl Animation {
-    pub fn new(length: f64, scale: f64) -> Animation {
-        Animation {
-            pos: Point::new(0.0, 0.0),
-            length,
-            duration: 0.0,
-            playing: false,
-            scale,
+    pub fn draw_debug(&self, c: piston_window::Context, g: &mut G2d) -> () {
+        let color = [1.0, 0.0, 0.0, 0.5];
+        match (self.state, self.shape) {
+            (ColliderState::Enabled, Shape::Circle(r)) => {
+                let rect = [self.pos.x - r, self.pos.y - r, r * 2.0, r * 2.0];
+                ellipse(color, rect, c.transform, g);
+            }
+            (ColliderState::Enabled, _) => {
+                let outline: Vec<[f64; 2]> = self.outline().iter().map(|p| [p.x, p.y]).collect();
+                polygon(color, &outline, c.transform, g);
+            }
+            (ColliderState::Disabled, _) => {}
         }
     }
 
-    pub fn draw(&self, anim_tex: &mut AnimTexture, c: piston_window::Context, g: &mut G2d) -> () {
-        if self.playing == false {
-            return;
-        };
-        let idx = ((self.duration / self.length) * (anim_tex.frames.len() as f64)).floor() as usize;
-        let frame = anim_tex.frames[idx];
-        Image::new().src_rect(frame).draw(
-            &anim_tex.texture,
-            &c.draw_state,
-            c.transform
-                .trans(
-                    -0.5 * self.scale * frame[2] + self.pos.x,
-                    -0.5 * self.scale * frame[3] + self.pos.y,
-                )
-                .zoom(self.scale),
-            g,
-        );
+    // Points round the edge of the shape, with rounded ends drawn as short straight lines
+    fn outline(&self) -> Vec<Point> {
+        use settings::collision::DEBUG_ARC_POINTS;
+
+        let (core, r) = self.core();
+        match self.shape {
+            Shape::Polygon(_) => core,
+            _ => {
+                // Half a circle round each end, starting from the side facing away from the
+                // other end
+                let ends = [(core[core.len() - 1], self.rot - 90.0), (core[0], self.rot + 90.0)];
+                ends.iter()
+                    .flat_map(|&(centre, from)| {
+                        (0..=DEBUG_ARC_POINTS).map(move |i| {
+                            let angle = from + 180.0 * i as f64 / DEBUG_ARC_POINTS as f64;
+                            let (sin, cos) = angle.to_radians().sin_cos();
+                            centre + Point::new(cos, sin) * r
+                        })
+                    })
+                    .collect()
+            }
+        }
     }
 
-    pub fn draw_at_pos(
-        &self,
-        anim_tex: &mut AnimTexture,
-        pos: Point,
-        scale: f64,
-        c: piston_window::Context,
-        g: &mut G2d,
-    ) -> () {
-        if self.playing == false {
-            return;
-        };
-        let idx = ((self.duration / self.length) * (anim_tex.frames.len() as f64)).floor() as usize;
-        let frame = anim_tex.frames[idx];
-        let final_scale = self.scale * scale;
-        Image::new().src_rect(frame).draw(
-            &anim_tex.texture,
-            &c.draw_state,
-            c.transform
-                .trans(
-                    -0.5 * final_scale * frame[2] + pos.x,
-                    -0.5 * final_scale * frame[3] + pos.y,
-                )
-                .zoom(final_scale),
-            g,
-        );
+    pub fn enable(&mut self) -> () {
+        self.state = ColliderState::Enabled;
     }
 
-    pub fn update(&mut self, dt: f64) -> () {
-        if self.playing == false {
-            return;
-        };
-        self.duration = self.duration + dt;
-        if self.duration > self.length {
-            self.stop();
-        }
+    pub fn disable(&mut self) -> () {
+        self.state = ColliderState::Disabled;
     }
+}
 
-    pub fn play(&mut self) -> () {
-        self.duration = 0.0;
-        self.playing = true;
+// Distance between two convex cores, or minus how far they overlap
+fn core_distance(a: &[Point], b: &[Point]) -> f64 {
+    // Points and lines can only meet by crossing, which the distance check below catches, but a
+    // polygon can also swallow the other core whole
+    if a.len() > 2 || b.len() > 2 {
+        if let Some(depth) = overlap(a, b) {
+            return -depth;
+        }
     }
 
-    pub fn stop(&mut self) -> () {
-        self.playing = false;
-        self.duration = 0.0;
+    let mut distance = f64::INFINITY;
+    for &edge in edges(a).iter() {
+        for &other_edge in edges(b).iter() {
+            distance = distance.min(segment_distance(edge, other_edge));
+        }
     }
+    distance
+}
 
-    pub fn set_pos(&mut self, pos: Point) -> () {
-        self.pos = pos;
+// Separating axis test. Two convex shapes miss each other if, and only if, there's a line out
+// from one of their edges that their shadows don't overlap on. Returns how far they overlap
+// along the line where they overlap least, or nothing if they're apart.
+fn overlap(a: &[Point], b: &[Point]) -> Option<f64> {
+    let mut least = f64::INFINITY;
+    for &(p, q) in edges(a).iter().chain(edges(b).iter()) {
+        let axis = Point::new(p.y - q.y, q.x - p.x).normalized();
+        if axis.magnitude() == 0.0 {
+            continue;
+        }
+
+        let (a_min, a_max) = project(a, axis);
+        let (b_min, b_max) = project(b, axis);
+        if a_max <= b_min || b_max <= a_min {
+            return None;
+        }
+        // How far one would have to be pushed along the line to clear the other
+        least = least.min((a_max - b_min).min(b_max - a_min));
     }
+    Some(least)
+}
 
-    pub fn is_playing(&self) -> bool {
-        self.playing
+fn project(points: &[Point], axis: Point) -> (f64, f64) {
+    points
+        .iter()
+        .map(|p| p.dot(axis))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), d| {
+            (min.min(d), max.max(d))
+        })
+}
+
+// A lone point is an edge with no length, and two points make a single edge
+fn edges(points: &[Point]) -> Vec<(Point, Point)> {
+    match points.len() {
+        1 => vec![(points[0], points[0])],
+        2 => vec![(points[0], points[1])],
+        n => (0..n).map(|i| (points[i], points[(i + 1) % n])).collect(),
     }
 }
 
-pub fn load_sprite(
-    window: &mut PistonWindow,
-    folder: &::std::path::PathBuf,
-    file: &str,
-    scale: f64,
-) -> Sprite<G2dTexture> {
-    let texture = Texture::from_path(
-        &mut window.factory,
-        folder.join(file),
-        Flip::None,
-        &TextureSettings::new(),
-    ).unwrap();
-    let mut spr = Sprite::from_texture(::std::rc::Rc::new(texture));
-    spr.set_scale(scale, scale);
-    spr
+// Shortest distance between two line segments, either of which can be a single point
+fn segment_distance((p1, q1): (Point, Point), (p2, q2): (Point, Point)) -> f64 {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.dot(d1);
+    let e = d2.dot(d2);
+    let f = d2.dot(r);
+    let clamp = |x: f64| x.clamp(0.0, 1.0);
+
+    // How far along each segment the closest points are
+    let (s, t) = if a == 0.0 && e == 0.0 {
+        (0.0, 0.0)
+    } else if a == 0.0 {
+        (0.0, clamp(f / e))
+    } else {
+        let c = d1.dot(r);
+        if e == 0.0 {
+            (clamp(-c / a), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            // Parallel segments can take any point on the first
+            let s = if denom != 0.0 {
+                clamp((b * f - c * e) / denom)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                (clamp(-c / a), 0.0)
+            } else if t > 1.0 {
+                (clamp((b - c) / a), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    ((p1 + d1 * s) - (p2 + d2 * t)).magnitude()
 }
 
 pub fn load_texture(
@@ -300,12 +493,13 @@ pub fn load_texture(
     folder: &::std::path::PathBuf,
     file: &str,
 ) -> G2dTexture {
+    // Anything that fails here has already been reported by the asset validation pass
     Texture::from_path(
         &mut window.factory,
         folder.join(file),
         Flip::None,
         &TextureSettings::new(),
-    ).unwrap()
+    ).unwrap_or_else(|_| placeholder_texture(window))
 }
 
 pub fn explosion_collisions(player: &mut Player, missiles: &mut Vec<Missile>) -> u32 {
@@ -314,18 +508,14 @@ pub fn explosion_collisions(player: &mut Player, missiles: &mut Vec<Missile>) ->
     let mut missile_explosion_count = 0;
 
     // Check for missile collisions
-    for (i, obj_i) in missiles.iter().enumerate() {
-        for (j, obj_j) in missiles[(i + 1)..].iter().enumerate() {
-            if obj_i.collides_with(obj_j) {
-                missile_collisions[i] = true;
-                missile_collisions[i + j + 1] = true;
-            }
-        }
+    for (i, j) in colliding_pairs(&missiles[..]) {
+        missile_collisions[i] = true;
+        missile_collisions[j] = true;
     }
 
     // Check for player and missile collision
     for (i, missile) in missiles.iter().enumerate() {
-        if player.collides_with(missile) {
+        if player.sweeps(missile) {
             player_collision = true;
             missile_collisions[i] = true;
         }
@@ -348,6 +538,31 @@ pub fn explosion_collisions(player: &mut Player, missiles: &mut Vec<Missile>) ->
     missile_explosion_count
 }
 
+// Closest gaps of the missiles that have just flown clear of the player after coming within the
+// near miss margin. Run after explosion_collisions, so missiles that hit have already stopped
+// being active.
+pub fn near_misses(player: &Player, missiles: &mut [Missile]) -> Vec<f64> {
+    use settings::near_miss::MARGIN;
+
+    if !player.is_active() {
+        return vec![];
+    }
+
+    let mut gaps = vec![];
+    for missile in missiles.iter_mut().filter(|m| m.is_active()) {
+        let gap = if player.is_near(missile, MARGIN) {
+            Some(player.collider.gap(&missile.collider).max(0.0))
+        } else {
+            None
+        };
+        if let Some(gap) = missile.pass_player(gap) {
+            gaps.push(gap);
+        }
+    }
+
+    gaps
+}
+
 pub fn collect_collisions(player: &Player, pickups: &mut Vec<Pickup>) -> u32 {
     let mut pickups_collected_count = 0;
 
@@ -378,4 +593,137 @@ mod tests {
         let collider_2 = Collider::new(Point::new(-2.0, -2.0), 1.0);
         assert_eq!(collider_1.collides_with(&collider_2), false);
     }
+
+    #[test]
+    fn it_should_catch_colliders_passing_through_each_other() {
+        // Head on, far enough apart that neither frame overlaps
+        let mut collider_1 = Collider::new(Point::new(-10.0, 0.0), 1.0);
+        let mut collider_2 = Collider::new(Point::new(10.0, 0.5), 1.0);
+        collider_1.move_to(Point::new(10.0, 0.0));
+        collider_2.move_to(Point::new(-10.0, 0.5));
+        assert!(!collider_1.collides_with(&collider_2));
+        assert!(collider_1.sweeps(&collider_2));
+        assert!(collider_2.sweeps(&collider_1));
+
+        // Meeting in the middle, with their edges touching 1 unit either side of it
+        let toi = collider_1.time_of_impact(&collider_2).unwrap();
+        assert!((toi - (0.5 - 1.936 / 40.0)).abs() < 0.001);
+
+        collider_2.disable();
+        assert!(!collider_1.sweeps(&collider_2));
+    }
+
+    #[test]
+    fn it_should_not_sweep_side_by_side_movers() {
+        let mut collider_1 = Collider::new(Point::new(0.0, 0.0), 1.0);
+        let mut collider_2 = Collider::new(Point::new(0.0, 3.0), 1.0);
+        collider_1.move_to(Point::new(50.0, 0.0));
+        collider_2.move_to(Point::new(50.0, 3.0));
+        assert!(!collider_1.sweeps(&collider_2));
+
+        // Placing a collider doesn't sweep the space it jumped over
+        collider_2.place(Point::new(25.0, 0.0));
+        collider_1.place(Point::new(0.0, 0.0));
+        assert!(!collider_1.sweeps(&collider_2));
+    }
+
+    const SQUARE: &[Point] = &[
+        Point { x: 1.0, y: 1.0 },
+        Point { x: -1.0, y: 1.0 },
+        Point { x: -1.0, y: -1.0 },
+        Point { x: 1.0, y: -1.0 },
+    ];
+
+    fn capsule(pos: Point, rot: f64) -> Collider {
+        let mut collider = Collider::with_shape(
+            pos,
+            Shape::Capsule {
+                half_length: 10.0,
+                r: 2.0,
+            },
+        );
+        collider.set_rotation(rot);
+        collider
+    }
+
+    #[test]
+    fn it_should_collide_circles_with_capsules() {
+        let collider = capsule(Point::new(0.0, 0.0), 0.0);
+        assert!(collider.collides_with(&Collider::new(Point::new(11.0, 0.0), 1.0)));
+        assert!(!collider.collides_with(&Collider::new(Point::new(0.0, 3.5), 1.0)));
+        assert!((collider.gap(&Collider::new(Point::new(5.0, 4.0), 1.0)) - 1.0).abs() < 1e-9);
+
+        // Turned to point straight down
+        let collider = capsule(Point::new(0.0, 0.0), 90.0);
+        assert!(collider.collides_with(&Collider::new(Point::new(0.0, 11.0), 1.0)));
+        assert!(!collider.collides_with(&Collider::new(Point::new(11.0, 0.0), 1.0)));
+        assert!((collider.gap(&capsule(Point::new(8.0, 0.0), 90.0)) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_separate_polygons_on_any_axis() {
+        let square = Collider::with_shape(Point::new(0.0, 0.0), Shape::Polygon(SQUARE));
+        let mut diamond = Collider::with_shape(Point::new(2.3, 0.0), Shape::Polygon(SQUARE));
+        diamond.set_rotation(45.0);
+        assert!(square.collides_with(&diamond));
+
+        // Their corners' circles still overlap, but there's a gap between the edges
+        diamond.place(Point::new(2.5, 0.0));
+        assert!(!square.collides_with(&diamond));
+        assert!((square.gap(&diamond) - (1.5 - 2.0f64.sqrt())).abs() < 1e-9);
+
+        // Swallowed whole, with no edges crossing
+        let inside = Collider::new(Point::new(0.2, 0.1), 0.5);
+        assert!(square.collides_with(&inside));
+        assert!(inside.collides_with(&square));
+    }
+
+    #[test]
+    fn it_should_sweep_rotated_shapes() {
+        let square = Collider::with_shape(Point::new(0.0, 0.0), Shape::Polygon(SQUARE));
+        let mut collider = capsule(Point::new(-40.0, 0.0), 90.0);
+        collider.move_to(Point::new(40.0, 0.0));
+        assert!(!collider.collides_with(&square));
+        assert!(collider.sweeps(&square));
+        let toi = collider.time_of_impact(&square).unwrap();
+        assert!((toi - 37.0 / 80.0).abs() < 0.001);
+
+        // Lying flat it passes underneath
+        let mut collider = capsule(Point::new(-40.0, 3.5), 0.0);
+        collider.move_to(Point::new(40.0, 3.5));
+        assert!(!collider.sweeps(&square));
+    }
+
+    #[test]
+    fn it_should_bound_rotated_shapes() {
+        let mut collider = capsule(Point::new(0.0, 0.0), 90.0);
+        let bounds = collider.bounds().unwrap();
+        let expected = [-2.0, -12.0, 2.0, 12.0];
+        assert!(bounds.iter().zip(expected.iter()).all(|(a, b)| (a - b).abs() < 1e-9));
+
+        collider.disable();
+        assert!(collider.bounds().is_none());
+    }
+
+    #[test]
+    fn it_should_detect_nearby_colliders() {
+        let collider_1 = Collider::new(Point::new(0.0, 0.0), 1.0);
+        let mut collider_2 = Collider::new(Point::new(4.0, 0.0), 1.0);
+        assert!(collider_1.is_near(&collider_2, 2.5));
+        assert!(!collider_1.is_near(&collider_2, 1.5));
+
+        collider_2.disable();
+        assert!(!collider_1.is_near(&collider_2, 2.5));
+    }
+
+    #[test]
+    fn it_should_measure_gap_between_edges() {
+        let collider_1 = Collider::new(Point::new(0.0, 0.0), 1.0);
+        let collider_2 = Collider::new(Point::new(5.0, 0.0), 2.0);
+        assert_approx_eq!(collider_1.gap(&collider_2), 2.0);
+        assert_approx_eq!(collider_2.gap(&collider_1), 2.0);
+
+        let collider_3 = Collider::new(Point::new(1.0, 0.0), 1.0);
+        assert!(collider_1.gap(&collider_3) < 0.0);
+    }
 }
@@ -0,0 +1,387 @@
+extern crate piston_window;
+
+use controls::GameAction;
+use game::Point;
+use piston_window::*;
+
+pub type DeviceId = i32;
+
+// Disconnected is part of the backend contract, though the Piston backend can't send it
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DeviceEvent {
+    Connected(DeviceId),
+    Disconnected(DeviceId),
+    Button(DeviceId, u8, bool), // (device, button, pressed)
+    Axis(DeviceId, u8, f64),    // (device, axis, position)
+}
+
+// Source of raw controller events, so the real window backend can be swapped for a simulated one
+pub trait DeviceBackend {
+    fn event(&mut self, _e: &Event) -> () {}
+
+    // Device events received since the last poll
+    fn poll(&mut self) -> Vec<DeviceEvent>;
+}
+
+// Reads controllers through the window's event loop. Piston gives no connection events, so a
+// controller counts as plugged in from the first event it sends. It can't tell when one is
+// pulled out either, so this backend never reports a disconnect: an unplugged pad stays
+// connected, still holding whatever it held. Releasing actions and pausing on unplug only
+// happen with a backend that reports disconnects.
+pub struct PistonBackend {
+    devices: Vec<DeviceId>,
+    pending: Vec<DeviceEvent>,
+}
+
+impl PistonBackend {
+    pub fn new() -> PistonBackend {
+        PistonBackend {
+            devices: vec![],
+            pending: vec![],
+        }
+    }
+
+    fn saw(&mut self, id: DeviceId) -> () {
+        if !self.devices.contains(&id) {
+            self.devices.push(id);
+            self.pending.push(DeviceEvent::Connected(id));
+        }
+    }
+}
+
+impl DeviceBackend for PistonBackend {
+    fn event(&mut self, e: &Event) -> () {
+        if let Some(Button::Controller(button)) = e.press_args() {
+            self.saw(button.id);
+            self.pending.push(DeviceEvent::Button(button.id, button.button, true));
+        }
+
+        if let Some(Button::Controller(button)) = e.release_args() {
+            self.saw(button.id);
+            self.pending.push(DeviceEvent::Button(button.id, button.button, false));
+        }
+
+        if let Some(args) = e.controller_axis_args() {
+            self.saw(args.id);
+            self.pending.push(DeviceEvent::Axis(args.id, args.axis, args.position));
+        }
+    }
+
+    fn poll(&mut self) -> Vec<DeviceEvent> {
+        ::std::mem::take(&mut self.pending)
+    }
+}
+
+// Plays back one batch of device events per poll, standing in for real hardware
+#[cfg(test)]
+pub struct SimulatedBackend {
+    frames: Vec<Vec<DeviceEvent>>,
+}
+
+#[cfg(test)]
+impl SimulatedBackend {
+    pub fn new(frames: Vec<Vec<DeviceEvent>>) -> SimulatedBackend {
+        SimulatedBackend { frames }
+    }
+}
+
+#[cfg(test)]
+impl DeviceBackend for SimulatedBackend {
+    fn poll(&mut self) -> Vec<DeviceEvent> {
+        if self.frames.is_empty() {
+            vec![]
+        } else {
+            self.frames.remove(0)
+        }
+    }
+}
+
+// Buttons and the stick are held separately, so letting go of one doesn't release an action
+// the other is still holding
+struct Gamepad {
+    id: DeviceId,
+    buttons: Vec<GameAction>,
+    stick_action: Option<GameAction>,
+    stick_pos: Point, // Where the stick is pushed, each axis from -1 to 1
+}
+
+impl Gamepad {
+    fn new(id: DeviceId) -> Gamepad {
+        Gamepad {
+            id,
+            buttons: vec![],
+            stick_action: None,
+            stick_pos: Point::new(0.0, 0.0),
+        }
+    }
+
+    fn holds(&self, action: GameAction) -> bool {
+        self.buttons.contains(&action) || self.stick_action == Some(action)
+    }
+
+    // Every action held by a button or the stick
+    fn held(&self) -> Vec<GameAction> {
+        let mut held = self.buttons.clone();
+        if let Some(action) = self.stick_action {
+            if !held.contains(&action) {
+                held.push(action);
+            }
+        }
+        held
+    }
+
+    fn set(&mut self, action: GameAction, pressed: bool, actions: &mut Vec<(GameAction, bool)>) {
+        let was_held = self.holds(action);
+        self.buttons.retain(|&a| a != action);
+        if pressed {
+            self.buttons.push(action);
+        }
+        if self.holds(action) != was_held {
+            actions.push((action, !was_held));
+        }
+    }
+
+    fn stick(&mut self, position: f64, actions: &mut Vec<(GameAction, bool)>) -> () {
+        use settings::gamepad::STICK_TURN_THRESHOLD;
+
+        let stick_action = if position < -STICK_TURN_THRESHOLD {
+            Some(GameAction::TurnLeft)
+        } else if position > STICK_TURN_THRESHOLD {
+            Some(GameAction::TurnRight)
+        } else {
+            None
+        };
+
+        if stick_action != self.stick_action {
+            // Release the old direction before pressing the new one
+            let changed: Vec<(GameAction, bool)> = self.stick_action
+                .into_iter()
+                .chain(stick_action)
+                .map(|action| (action, self.holds(action)))
+                .collect();
+            self.stick_action = stick_action;
+            for (action, was_held) in changed {
+                if self.holds(action) != was_held {
+                    actions.push((action, !was_held));
+                }
+            }
+        }
+    }
+}
+
+fn button_action(button: u8) -> Option<GameAction> {
+    use settings::gamepad::*;
+
+    match button {
+        b if b == DPAD_LEFT => Some(GameAction::TurnLeft),
+        b if b == DPAD_RIGHT => Some(GameAction::TurnRight),
+        b if b == BUTTON_RESTART => Some(GameAction::Restart),
+        b if b == BUTTON_BOOST => Some(GameAction::Boost),
+        b if b == BUTTON_PAUSE => Some(GameAction::Pause),
+        _ => None,
+    }
+}
+
+// Turns controller input into the same actions as the keyboard bindings
+pub struct Gamepads {
+    backend: Box<dyn DeviceBackend>,
+    pads: Vec<Gamepad>,
+}
+
+impl Gamepads {
+    pub fn new(backend: Box<dyn DeviceBackend>) -> Gamepads {
+        Gamepads {
+            backend,
+            pads: vec![],
+        }
+    }
+
+    pub fn event(&mut self, e: &Event) -> () {
+        self.backend.event(e);
+    }
+
+    // Actions pressed (true) or released (false) since the last poll
+    pub fn poll(&mut self) -> Vec<(GameAction, bool)> {
+        use settings::input::{STICK_X_AXIS, STICK_Y_AXIS};
+
+        let mut actions = vec![];
+
+        for event in self.backend.poll() {
+            match event {
+                DeviceEvent::Connected(id) => {
+                    self.pad(id);
+                }
+                DeviceEvent::Disconnected(id) => {
+                    // Release everything the pad was holding so the ship doesn't keep turning
+                    if let Some(idx) = self.pads.iter().position(|pad| pad.id == id) {
+                        let pad = self.pads.remove(idx);
+                        for action in pad.held() {
+                            actions.push((action, false));
+                        }
+                    }
+                }
+                DeviceEvent::Button(id, button, pressed) => {
+                    if let Some(action) = button_action(button) {
+                        self.pad(id).set(action, pressed, &mut actions);
+                    }
+                }
+                DeviceEvent::Axis(id, axis, position) => {
+                    let pad = self.pad(id);
+                    if axis == STICK_X_AXIS {
+                        pad.stick_pos.x = position;
+                        pad.stick(position, &mut actions);
+                    } else if axis == STICK_Y_AXIS {
+                        pad.stick_pos.y = position;
+                    }
+                }
+            }
+        }
+
+        actions
+    }
+
+    pub fn connected(&self) -> usize {
+        self.pads.len()
+    }
+
+    // The stick pushed furthest across every connected pad, for steering by stick angle
+    pub fn stick(&self) -> Point {
+        self.pads
+            .iter()
+            .map(|pad| pad.stick_pos)
+            .fold(Point::new(0.0, 0.0), |furthest, stick| {
+                if stick.magnitude() > furthest.magnitude() {
+                    stick
+                } else {
+                    furthest
+                }
+            })
+    }
+
+    fn pad(&mut self, id: DeviceId) -> &mut Gamepad {
+        let idx = match self.pads.iter().position(|pad| pad.id == id) {
+            Some(idx) => idx,
+            None => {
+                self.pads.push(Gamepad::new(id));
+                self.pads.len() - 1
+            }
+        };
+        &mut self.pads[idx]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use settings::gamepad::*;
+    use settings::input::STICK_X_AXIS;
+
+    fn simulate(frames: Vec<Vec<DeviceEvent>>) -> Gamepads {
+        Gamepads::new(Box::new(SimulatedBackend::new(frames)))
+    }
+
+    #[test]
+    fn it_should_map_buttons_to_actions() {
+        let mut gamepads = simulate(vec![vec![
+            DeviceEvent::Connected(0),
+            DeviceEvent::Button(0, BUTTON_RESTART, true),
+            DeviceEvent::Button(0, BUTTON_RESTART, false),
+            DeviceEvent::Button(0, BUTTON_PAUSE, true),
+        ]]);
+
+        assert_eq!(
+            gamepads.poll(),
+            vec![
+                (GameAction::Restart, true),
+                (GameAction::Restart, false),
+                (GameAction::Pause, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_track_hot_plugged_pads() {
+        let mut gamepads = simulate(vec![
+            vec![DeviceEvent::Connected(0)],
+            vec![DeviceEvent::Connected(1)],
+            vec![DeviceEvent::Disconnected(0)],
+        ]);
+
+        gamepads.poll();
+        assert_eq!(gamepads.connected(), 1);
+        gamepads.poll();
+        assert_eq!(gamepads.connected(), 2);
+        gamepads.poll();
+        assert_eq!(gamepads.connected(), 1);
+    }
+
+    #[test]
+    fn it_should_release_held_actions_on_disconnect() {
+        let mut gamepads = simulate(vec![
+            vec![DeviceEvent::Button(3, DPAD_LEFT, true)],
+            vec![DeviceEvent::Disconnected(3)],
+        ]);
+
+        assert_eq!(gamepads.poll(), vec![(GameAction::TurnLeft, true)]);
+        assert_eq!(gamepads.poll(), vec![(GameAction::TurnLeft, false)]);
+    }
+
+    #[test]
+    fn it_should_connect_pads_on_their_first_event() {
+        let mut backend = PistonBackend::new();
+        backend.saw(3);
+        backend.saw(3);
+        backend.saw(4);
+        assert_eq!(
+            backend.poll(),
+            vec![DeviceEvent::Connected(3), DeviceEvent::Connected(4)]
+        );
+        assert_eq!(backend.poll(), vec![]);
+    }
+
+    #[test]
+    fn it_should_keep_dpad_held_when_stick_returns() {
+        let mut gamepads = simulate(vec![vec![
+            DeviceEvent::Button(0, DPAD_LEFT, true),
+            DeviceEvent::Axis(0, STICK_X_AXIS, -1.0),
+            DeviceEvent::Axis(0, STICK_X_AXIS, 0.0),
+            DeviceEvent::Button(0, DPAD_LEFT, false),
+            DeviceEvent::Axis(0, STICK_X_AXIS, 1.0),
+            DeviceEvent::Button(0, DPAD_RIGHT, true),
+            DeviceEvent::Button(0, DPAD_RIGHT, false),
+            DeviceEvent::Axis(0, STICK_X_AXIS, 0.0),
+        ]]);
+
+        assert_eq!(
+            gamepads.poll(),
+            vec![
+                (GameAction::TurnLeft, true),
+                (GameAction::TurnLeft, false),
+                (GameAction::TurnRight, true),
+                (GameAction::TurnRight, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_steer_with_stick_past_threshold() {
+        let mut gamepads = simulate(vec![vec![
+            DeviceEvent::Axis(0, STICK_X_AXIS, STICK_TURN_THRESHOLD * 0.5),
+            DeviceEvent::Axis(0, STICK_X_AXIS, 1.0),
+            DeviceEvent::Axis(0, STICK_X_AXIS, 0.9),
+            DeviceEvent::Axis(0, STICK_X_AXIS, -1.0),
+            DeviceEvent::Axis(0, STICK_X_AXIS, 0.0),
+        ]]);
+
+        assert_eq!(
+            gamepads.poll(),
+            vec![
+                (GameAction::TurnRight, true),
+                (GameAction::TurnRight, false),
+                (GameAction::TurnLeft, true),
+                (GameAction::TurnLeft, false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use game::Score;
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub score: Score,
+    pub ship: String,
+    pub speed: u32, // Game speed percentage the score was set at
+}
+
+pub struct HighScores {
+    path: PathBuf,
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn load(path: PathBuf) -> HighScores {
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => parse_entries(&contents),
+            Err(_) => vec![],
+        };
+
+        HighScores { path, entries }
+    }
+
+    // Returns true if the score made it onto the table for its game speed
+    pub fn submit(&mut self, score: Score, ship: &str, speed: u32) -> bool {
+        use settings::high_score::MAX_ENTRIES;
+
+        let rank = self.entries
+            .iter()
+            .filter(|e| e.speed == speed && e.score >= score)
+            .count();
+
+        if rank >= MAX_ENTRIES {
+            return false;
+        }
+
+        let entry = HighScore {
+            score,
+            ship: ship.to_owned(),
+            speed,
+        };
+        let idx = self.entries
+            .iter()
+            .position(|e| e.score < score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(idx, entry);
+
+        // Drop whatever got pushed off the bottom of this speed's table
+        let mut kept = 0;
+        self.entries.retain(|e| {
+            if e.speed != speed {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_ENTRIES
+        });
+        true
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(&self.path, format_entries(&self.entries))
+    }
+
+    // The leaderboard for one game speed, best first
+    pub fn entries(&self, speed: u32) -> Vec<&HighScore> {
+        self.entries.iter().filter(|e| e.speed == speed).collect()
+    }
+}
+
+// Each line is stored as "score,ship,speed". Scores saved before game speed was added have no
+// speed and were set at full speed.
+fn parse_entries(contents: &str) -> Vec<HighScore> {
+    let mut entries: Vec<HighScore> = contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let score = fields.next()?.trim().parse::<Score>().ok()?;
+            let ship = fields.next()?.trim().to_owned();
+            let speed = match fields.next() {
+                Some(speed) => speed.trim().parse::<u32>().ok()?,
+                None => 100,
+            };
+            Some(HighScore { score, ship, speed })
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| Reverse(entry.score));
+    entries
+}
+
+fn format_entries(entries: &[HighScore]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("{},{},{}\n", e.score, e.ship, e.speed))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_round_trip_entries() {
+        let entries = vec![
+            HighScore {
+                score: 1200,
+                ship: "Fighter".to_owned(),
+                speed: 100,
+            },
+            HighScore {
+                score: 300,
+                ship: "Balanced".to_owned(),
+                speed: 70,
+            },
+        ];
+        assert_eq!(parse_entries(&format_entries(&entries)), entries);
+    }
+
+    #[test]
+    fn it_should_skip_malformed_lines() {
+        let entries = parse_entries("100,Fighter,80\nnot a score\n,\n200,Interceptor,fast\n");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].speed, 80);
+    }
+
+    #[test]
+    fn it_should_read_scores_without_speed_as_full_speed() {
+        let entries = parse_entries("100,Fighter\n");
+        assert_eq!(entries[0].speed, 100);
+    }
+
+    #[test]
+    fn it_should_keep_scores_sorted_and_capped() {
+        use settings::high_score::MAX_ENTRIES;
+
+        let mut scores = HighScores {
+            path: PathBuf::new(),
+            entries: vec![],
+        };
+        for score in 0..(MAX_ENTRIES as Score + 5) {
+            scores.submit(score, "Balanced", 100);
+        }
+        assert_eq!(scores.entries(100).len(), MAX_ENTRIES);
+        assert_eq!(scores.entries(100)[0].score, MAX_ENTRIES as Score + 4);
+        assert!(!scores.submit(0, "Balanced", 100));
+    }
+
+    #[test]
+    fn it_should_keep_separate_tables_per_speed() {
+        use settings::high_score::MAX_ENTRIES;
+
+        let mut scores = HighScores {
+            path: PathBuf::new(),
+            entries: vec![],
+        };
+        for score in 0..(MAX_ENTRIES as Score) {
+            scores.submit(score + 100, "Balanced", 100);
+        }
+        assert!(scores.submit(1, "Fighter", 50));
+        assert_eq!(scores.entries(50).len(), 1);
+        assert_eq!(scores.entries(100).len(), MAX_ENTRIES);
+    }
+}
@@ -0,0 +1,434 @@
+extern crate piston_window;
+
+use controls::GameAction;
+use game::*;
+use piston_window::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Steering {
+    Digital(KeyState, KeyState), // (left, right)
+    Heading(f64),                // Degrees, 0 is along the positive x axis
+    Neutral,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InputMode {
+    Keyboard,
+    Mouse,
+    Analog,
+    OneButtonToggle,
+    OneButtonHold,
+}
+
+pub const INPUT_MODES: [InputMode; 5] = [
+    InputMode::Keyboard,
+    InputMode::Mouse,
+    InputMode::Analog,
+    InputMode::OneButtonToggle,
+    InputMode::OneButtonHold,
+];
+
+impl InputMode {
+    // Name used in the config file
+    pub fn config_name(&self) -> &'static str {
+        match *self {
+            InputMode::Keyboard => "keyboard",
+            InputMode::Mouse => "mouse",
+            InputMode::Analog => "analog",
+            InputMode::OneButtonToggle => "one_button_toggle",
+            InputMode::OneButtonHold => "one_button_hold",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<InputMode> {
+        INPUT_MODES.iter().find(|m| m.config_name() == name).cloned()
+    }
+
+    // Whether the mode steers with the restart button alone, so menus step through their
+    // choices for the player instead of waiting for other keys
+    pub fn is_one_button(&self) -> bool {
+        *self == InputMode::OneButtonToggle || *self == InputMode::OneButtonHold
+    }
+
+    pub fn next(&self) -> InputMode {
+        let idx = INPUT_MODES.iter().position(|m| m == self).unwrap();
+        INPUT_MODES[(idx + 1) % INPUT_MODES.len()]
+    }
+}
+
+pub trait InputSource {
+    fn event(&mut self, _e: &Event) -> () {}
+
+    fn action(&mut self, _action: GameAction, _pressed: bool) -> () {}
+
+    // Where the gamepad stick is pushed, as read by the gamepad backend
+    fn stick(&mut self, _position: Point) -> () {}
+
+    // Whether the source uses this action for steering, so it shouldn't also do its usual job
+    fn captures(&self, _action: GameAction) -> bool {
+        false
+    }
+
+    fn reset(&mut self) -> () {}
+
+    fn update(&mut self, _dt: f64) -> () {}
+
+    // The steering wanted for a ship positioned at `origin` on screen
+    fn steering(&self, origin: Point) -> Steering;
+}
+
+pub fn new_input_source(mode: InputMode, hold_turns_right: bool) -> Box<dyn InputSource> {
+    match mode {
+        InputMode::Keyboard => Box::new(KeyboardInput::new()),
+        InputMode::Mouse => Box::new(MouseInput::new()),
+        InputMode::Analog => Box::new(AnalogInput::new()),
+        InputMode::OneButtonToggle => Box::new(ToggleInput::new()),
+        InputMode::OneButtonHold => Box::new(HoldInput::new(hold_turns_right)),
+    }
+}
+
+pub struct KeyboardInput {
+    left_key: KeyState,
+    right_key: KeyState,
+}
+
+impl KeyboardInput {
+    pub fn new() -> KeyboardInput {
+        KeyboardInput {
+            left_key: KeyState::NotPressed,
+            right_key: KeyState::NotPressed,
+        }
+    }
+}
+
+impl InputSource for KeyboardInput {
+    fn action(&mut self, action: GameAction, pressed: bool) -> () {
+        let key_state = if pressed {
+            KeyState::Pressed
+        } else {
+            KeyState::NotPressed
+        };
+
+        match action {
+            GameAction::TurnLeft => self.left_key = key_state,
+            GameAction::TurnRight => self.right_key = key_state,
+            _ => (),
+        }
+    }
+
+    fn steering(&self, _origin: Point) -> Steering {
+        Steering::Digital(self.left_key, self.right_key)
+    }
+}
+
+pub struct MouseInput {
+    cursor: Option<Point>,
+}
+
+impl MouseInput {
+    pub fn new() -> MouseInput {
+        MouseInput { cursor: None }
+    }
+}
+
+impl InputSource for MouseInput {
+    fn event(&mut self, e: &Event) -> () {
+        if let Some([x, y]) = e.mouse_cursor_args() {
+            self.cursor = Some(Point::new(x, y));
+        }
+    }
+
+    fn steering(&self, origin: Point) -> Steering {
+        use settings::input::MOUSE_DEADZONE;
+
+        match self.cursor {
+            Some(cursor) if (cursor - origin).magnitude() > MOUSE_DEADZONE => {
+                Steering::Heading(heading(cursor - origin))
+            }
+            _ => Steering::Neutral,
+        }
+    }
+}
+
+pub struct AnalogInput {
+    stick: Point,
+}
+
+impl AnalogInput {
+    pub fn new() -> AnalogInput {
+        AnalogInput {
+            stick: Point::new(0.0, 0.0),
+        }
+    }
+}
+
+impl InputSource for AnalogInput {
+    fn stick(&mut self, position: Point) -> () {
+        self.stick = position;
+    }
+
+    fn steering(&self, _origin: Point) -> Steering {
+        use settings::input::STICK_DEADZONE;
+
+        if self.stick.magnitude() > STICK_DEADZONE {
+            Steering::Heading(heading(self.stick))
+        } else {
+            Steering::Neutral
+        }
+    }
+}
+
+// One-switch control: each press of the restart button swaps between turning left and right
+pub struct ToggleInput {
+    turn: Steering,
+}
+
+impl ToggleInput {
+    pub fn new() -> ToggleInput {
+        ToggleInput {
+            turn: Steering::Neutral,
+        }
+    }
+}
+
+impl InputSource for ToggleInput {
+    fn action(&mut self, action: GameAction, pressed: bool) -> () {
+        if action != GameAction::Restart || !pressed {
+            return;
+        }
+
+        self.turn = match self.turn {
+            Steering::Digital(KeyState::Pressed, _) => {
+                Steering::Digital(KeyState::NotPressed, KeyState::Pressed)
+            }
+            _ => Steering::Digital(KeyState::Pressed, KeyState::NotPressed),
+        };
+    }
+
+    fn captures(&self, action: GameAction) -> bool {
+        action == GameAction::Restart
+    }
+
+    fn reset(&mut self) -> () {
+        self.turn = Steering::Neutral;
+    }
+
+    fn steering(&self, _origin: Point) -> Steering {
+        self.turn
+    }
+}
+
+// One-switch control: holding the restart button turns, releasing it flies straight
+pub struct HoldInput {
+    held: KeyState,
+    turns_right: bool,
+}
+
+impl HoldInput {
+    pub fn new(turns_right: bool) -> HoldInput {
+        HoldInput {
+            held: KeyState::NotPressed,
+            turns_right,
+        }
+    }
+}
+
+impl InputSource for HoldInput {
+    fn action(&mut self, action: GameAction, pressed: bool) -> () {
+        if action == GameAction::Restart {
+            self.held = if pressed {
+                KeyState::Pressed
+            } else {
+                KeyState::NotPressed
+            };
+        }
+    }
+
+    fn captures(&self, action: GameAction) -> bool {
+        action == GameAction::Restart
+    }
+
+    fn steering(&self, _origin: Point) -> Steering {
+        if self.turns_right {
+            Steering::Digital(KeyState::NotPressed, self.held)
+        } else {
+            Steering::Digital(self.held, KeyState::NotPressed)
+        }
+    }
+}
+
+fn heading(direction: Point) -> f64 {
+    direction.y.atan2(direction.x).to_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gamepad::{DeviceEvent, Gamepads, SimulatedBackend};
+    use player::{test_player, Player};
+    use settings::input::{STICK_X_AXIS, STICK_Y_AXIS};
+    use ship;
+
+    // Replays a list of (time, steering) changes instead of reading real devices
+    struct ScriptedInput {
+        script: Vec<(f64, Steering)>,
+        time: f64,
+    }
+
+    impl InputSource for ScriptedInput {
+        fn update(&mut self, dt: f64) -> () {
+            self.time += dt;
+        }
+
+        fn steering(&self, _origin: Point) -> Steering {
+            self.script
+                .iter()
+                .rfind(|&&(time, _)| time <= self.time)
+                .map_or(Steering::Neutral, |&(_, steering)| steering)
+        }
+    }
+
+    fn run(input: &mut ScriptedInput, player: &mut Player, steps: u32, dt: f64) {
+        for _ in 0..steps {
+            input.update(dt);
+            let steering = input.steering(player.collider.pos);
+            player.steer(steering);
+            player.update(dt);
+        }
+    }
+
+    fn player_heading(player: &Player) -> f64 {
+        heading(player.velocity())
+    }
+
+    #[test]
+    fn it_should_turn_towards_heading_at_angular_velocity() {
+        let mut player = test_player();
+        let mut input = ScriptedInput {
+            script: vec![(0.0, Steering::Heading(90.0))],
+            time: 0.0,
+        };
+        let turn_rate = ship::ROSTER[0].angular_velocity;
+
+        run(&mut input, &mut player, 1, 0.1);
+        assert_approx_eq!(player_heading(&player), turn_rate * 0.1);
+
+        run(&mut input, &mut player, 100, 0.1);
+        assert_approx_eq!(player_heading(&player), 90.0);
+    }
+
+    #[test]
+    fn it_should_take_the_shortest_way_round() {
+        let mut player = test_player();
+        let mut input = ScriptedInput {
+            script: vec![(0.0, Steering::Heading(-170.0))],
+            time: 0.0,
+        };
+
+        run(&mut input, &mut player, 100, 0.1);
+        assert_approx_eq!(player_heading(&player), -170.0);
+    }
+
+    #[test]
+    fn it_should_follow_scripted_changes() {
+        let mut player = test_player();
+        let mut input = ScriptedInput {
+            script: vec![
+                (0.0, Steering::Digital(KeyState::NotPressed, KeyState::Pressed)),
+                (0.45, Steering::Neutral),
+            ],
+            time: 0.0,
+        };
+        let turn_rate = ship::ROSTER[0].angular_velocity;
+
+        run(&mut input, &mut player, 10, 0.1);
+        assert_approx_eq!(player_heading(&player), turn_rate * 0.4);
+    }
+
+    #[test]
+    fn toggle_should_swap_turn_direction_on_each_press() {
+        let mut input = ToggleInput::new();
+        assert_eq!(input.steering(Point::new(0.0, 0.0)), Steering::Neutral);
+
+        input.action(GameAction::Restart, true);
+        input.action(GameAction::Restart, false);
+        assert_eq!(
+            input.steering(Point::new(0.0, 0.0)),
+            Steering::Digital(KeyState::Pressed, KeyState::NotPressed)
+        );
+
+        input.action(GameAction::Restart, true);
+        assert_eq!(
+            input.steering(Point::new(0.0, 0.0)),
+            Steering::Digital(KeyState::NotPressed, KeyState::Pressed)
+        );
+    }
+
+    #[test]
+    fn hold_should_straighten_on_release() {
+        let mut input = HoldInput::new(false);
+
+        input.action(GameAction::Restart, true);
+        assert_eq!(
+            input.steering(Point::new(0.0, 0.0)),
+            Steering::Digital(KeyState::Pressed, KeyState::NotPressed)
+        );
+
+        input.action(GameAction::Restart, false);
+        assert_eq!(
+            input.steering(Point::new(0.0, 0.0)),
+            Steering::Digital(KeyState::NotPressed, KeyState::NotPressed)
+        );
+    }
+
+    #[test]
+    fn it_should_round_trip_input_mode_names() {
+        for mode in INPUT_MODES.iter() {
+            assert_eq!(InputMode::from_config_name(mode.config_name()), Some(*mode));
+        }
+    }
+
+    #[test]
+    fn it_should_aim_at_mouse_cursor() {
+        let input = MouseInput {
+            cursor: Some(Point::new(10.0, 110.0)),
+        };
+        match input.steering(Point::new(10.0, 10.0)) {
+            Steering::Heading(angle) => assert_approx_eq!(angle, 90.0),
+            _ => panic!("Expected a heading"),
+        }
+    }
+
+    #[test]
+    fn it_should_ignore_stick_inside_deadzone() {
+        let input = AnalogInput {
+            stick: Point::new(0.05, 0.05),
+        };
+        assert_eq!(input.steering(Point::new(0.0, 0.0)), Steering::Neutral);
+    }
+
+    #[test]
+    fn it_should_steer_by_stick_angle_from_gamepads() {
+        let mut gamepads = Gamepads::new(Box::new(SimulatedBackend::new(vec![
+            vec![
+                DeviceEvent::Axis(0, STICK_X_AXIS, 0.1),
+                DeviceEvent::Axis(1, STICK_X_AXIS, 0.0),
+                DeviceEvent::Axis(1, STICK_Y_AXIS, 0.9),
+            ],
+            vec![DeviceEvent::Disconnected(1)],
+        ])));
+        let mut input = AnalogInput::new();
+
+        gamepads.poll();
+        input.stick(gamepads.stick());
+        match input.steering(Point::new(0.0, 0.0)) {
+            Steering::Heading(angle) => assert_approx_eq!(angle, 90.0),
+            _ => panic!("Expected a heading"),
+        }
+
+        // The pad left behind is inside the deadzone
+        gamepads.poll();
+        input.stick(gamepads.stick());
+        assert_eq!(input.steering(Point::new(0.0, 0.0)), Steering::Neutral);
+    }
+}
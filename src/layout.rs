@@ -0,0 +1,136 @@
+use fonts::Fonts;
+use game::*;
+
+// Which point of the screen, and of the thing being placed, line up
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    Left,
+    Centre,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    // How far across and down the anchor point is, from 0 to 1
+    fn fractions(&self) -> (f64, f64) {
+        match *self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Centre => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+// Top left corner of a box of the given size, anchored within an area. The margin pushes the box
+// in from the edges it's anchored to, and down or right along the axes it's centred on.
+pub fn place(anchor: Anchor, margin: Point, size: Point, area: Point) -> Point {
+    let (fx, fy) = anchor.fractions();
+    let inwards = |fraction: f64, margin: f64| {
+        if fraction > 0.5 {
+            -margin
+        } else {
+            margin
+        }
+    };
+
+    Point::new(
+        (area.x - size.x) * fx + inwards(fx, margin.x),
+        (area.y - size.y) * fy + inwards(fy, margin.y),
+    )
+}
+
+// Text is drawn from its baseline, so this is where to draw it to put the line's box at the anchor
+pub fn place_text(
+    text: &str,
+    anchor: Anchor,
+    margin: Point,
+    font_size: u32,
+    font: &mut Fonts,
+    area: Point,
+) -> Point {
+    let height = font_size as f64;
+    let size = Point::new(font.width(font_size, text), height);
+    place(anchor, margin, size, area) + Point::new(0.0, height)
+}
+
+// Top left corner of each column of a table, placed as one box so the columns stay together
+pub fn place_columns(
+    widths: &[f64],
+    gap: f64,
+    height: f64,
+    anchor: Anchor,
+    margin: Point,
+    area: Point,
+) -> Vec<Point> {
+    let gaps = gap * widths.len().saturating_sub(1) as f64;
+    let size = Point::new(widths.iter().sum::<f64>() + gaps, height);
+    let mut pos = place(anchor, margin, size, area);
+
+    let mut columns = vec![];
+    for &width in widths.iter() {
+        columns.push(pos);
+        pos.x += width + gap;
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Point = Point { x: 1280.0, y: 720.0 };
+
+    fn place_box(anchor: Anchor, margin: (f64, f64)) -> (f64, f64) {
+        let pos = place(
+            anchor,
+            Point::new(margin.0, margin.1),
+            Point::new(200.0, 100.0),
+            AREA,
+        );
+        (pos.x, pos.y)
+    }
+
+    #[test]
+    fn it_should_centre_box() {
+        assert_eq!(place_box(Anchor::Centre, (0.0, 0.0)), (540.0, 310.0));
+        assert_eq!(place_box(Anchor::Centre, (0.0, -50.0)), (540.0, 260.0));
+    }
+
+    #[test]
+    fn it_should_keep_margin_from_anchored_edges() {
+        assert_eq!(place_box(Anchor::TopLeft, (20.0, 10.0)), (20.0, 10.0));
+        assert_eq!(place_box(Anchor::BottomRight, (20.0, 10.0)), (1060.0, 610.0));
+        assert_eq!(place_box(Anchor::Bottom, (20.0, 10.0)), (560.0, 610.0));
+    }
+
+    #[test]
+    fn it_should_stay_centred_at_any_width() {
+        let size = Point::new(300.0, 50.0);
+        for &width in [960.0, 1280.0, 1440.0].iter() {
+            let pos = place(Anchor::Top, Point::new(0.0, 0.0), size, Point::new(width, 720.0));
+            assert_eq!(pos.x + size.x / 2.0, width / 2.0);
+        }
+    }
+
+    #[test]
+    fn it_should_place_columns_side_by_side() {
+        let columns = place_columns(
+            &[100.0, 200.0],
+            50.0,
+            300.0,
+            Anchor::Top,
+            Point::new(0.0, 40.0),
+            AREA,
+        );
+        assert_eq!(columns, vec![Point::new(465.0, 40.0), Point::new(615.0, 40.0)]);
+    }
+}
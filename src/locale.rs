@@ -0,0 +1,210 @@
+use assets::{AssetKind, AssetManifest};
+use game::Score;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Text shown to the player in one language, read from "key = text" lines. Text can hold {name}
+// placeholders that are filled in when it's shown.
+pub struct Locale {
+    code: String,
+    strings: HashMap<String, String>,
+}
+
+impl Locale {
+    pub fn parse(code: &str, contents: &str) -> (Locale, Vec<String>) {
+        let mut strings = HashMap::new();
+        let mut errors = vec![];
+
+        for (line_no, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.splitn(2, '=');
+            match (fields.next(), fields.next()) {
+                (Some(key), Some(text)) => {
+                    strings.insert(key.trim().to_owned(), text.trim().to_owned());
+                }
+                _ => errors.push(format!("Line {}: expected 'key = text'", line_no + 1)),
+            }
+        }
+
+        let locale = Locale {
+            code: code.to_owned(),
+            strings,
+        };
+        (locale, errors)
+    }
+
+    // Anything the locale doesn't translate is shown in the default language
+    pub fn load(folder: &Path, code: &str) -> (Locale, Vec<String>) {
+        use settings::locale::DEFAULT;
+
+        let mut locale = Locale {
+            code: code.to_owned(),
+            strings: HashMap::new(),
+        };
+        let mut errors = vec![];
+
+        let codes = if code == DEFAULT {
+            vec![DEFAULT]
+        } else {
+            vec![DEFAULT, code]
+        };
+        for code in codes {
+            let file = file(code);
+            match fs::read_to_string(folder.join(&file)) {
+                Ok(contents) => {
+                    let (loaded, problems) = Locale::parse(code, &contents);
+                    locale.strings.extend(loaded.strings);
+                    errors.extend(problems.into_iter().map(|p| format!("{}: {}", file, p)));
+                }
+                Err(err) => errors.push(format!("Could not read {}: {}", file, err)),
+            }
+        }
+
+        (locale, errors)
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    // The language's own name for itself
+    pub fn name(&self) -> &str {
+        self.get("locale.name")
+    }
+
+    // Missing text shows up as its key, so it's easy to spot
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map(|s| s.as_str()).unwrap_or(key)
+    }
+
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        args.iter().fold(self.get(key).to_owned(), |text, &(name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+    }
+
+    // Whole numbers with digits grouped in threes
+    pub fn number(&self, value: Score) -> String {
+        let digits = value.to_string();
+        let separator = self.strings.get("number.thousands").map_or("", |s| s.as_str());
+
+        // Split off groups from the right, leaving any short group at the front
+        let mut groups = vec![];
+        let mut end = digits.len();
+        while end > 3 {
+            groups.push(&digits[end - 3..end]);
+            end -= 3;
+        }
+        groups.push(&digits[..end]);
+        groups.reverse();
+        groups.join(separator)
+    }
+
+    pub fn decimal(&self, value: f64, places: usize) -> String {
+        let text = format!("{:.*}", places, value);
+        match self.strings.get("number.decimal") {
+            Some(point) => text.replace('.', point),
+            None => text,
+        }
+    }
+
+    // Fonts to fall back on for characters the main font lacks
+    pub fn fonts(&self) -> Vec<&str> {
+        self.strings
+            .get("fonts")
+            .map(|fonts| fonts.split(',').map(|f| f.trim()).collect())
+            .unwrap_or_default()
+    }
+}
+
+pub fn file(code: &str) -> String {
+    use settings::locale::FOLDER;
+
+    format!("{}/{}.txt", FOLDER, code)
+}
+
+fn code_of(file: &str) -> Option<&str> {
+    Path::new(file).file_stem().and_then(|stem| stem.to_str())
+}
+
+// (code, name) of every locale in the asset manifest
+pub fn available(folder: &Path, manifest: &AssetManifest) -> Vec<(String, String)> {
+    manifest
+        .files(AssetKind::Locale)
+        .iter()
+        .filter_map(|file| {
+            let code = code_of(file)?;
+            let contents = fs::read_to_string(folder.join(file)).ok()?;
+            let (locale, _) = Locale::parse(code, &contents);
+            Some((code.to_owned(), locale.name().to_owned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn german() -> Locale {
+        let (locale, errors) = Locale::parse(
+            "de",
+            "# German\nlocale.name = Deutsch\nnumber.thousands = .\nnumber.decimal = ,\n\
+             restart = {key} drücken, um nochmal zu spielen\nfonts = a.ttf, b.ttf\n",
+        );
+        assert!(errors.is_empty());
+        locale
+    }
+
+    #[test]
+    fn it_should_fill_in_placeholders() {
+        let locale = german();
+        assert_eq!(locale.name(), "Deutsch");
+        assert_eq!(
+            locale.format("restart", &[("key", "SPACE")]),
+            "SPACE drücken, um nochmal zu spielen"
+        );
+    }
+
+    #[test]
+    fn it_should_show_key_for_missing_text() {
+        let locale = german();
+        assert_eq!(locale.get("game_over"), "game_over");
+        assert_eq!(locale.format("score", &[("score", "10")]), "score");
+    }
+
+    #[test]
+    fn it_should_group_digits() {
+        let locale = german();
+        assert_eq!(locale.number(0), "0");
+        assert_eq!(locale.number(999), "999");
+        assert_eq!(locale.number(1000), "1.000");
+        assert_eq!(locale.number(1234567), "1.234.567");
+        assert_eq!(locale.decimal(2.5, 1), "2,5");
+
+        let (plain, _) = Locale::parse("xx", "");
+        assert_eq!(plain.number(1234567), "1234567");
+        assert_eq!(plain.decimal(1.5, 1), "1.5");
+    }
+
+    #[test]
+    fn it_should_list_fallback_fonts() {
+        assert_eq!(german().fonts(), vec!["a.ttf", "b.ttf"]);
+        assert!(Locale::parse("xx", "").0.fonts().is_empty());
+    }
+
+    #[test]
+    fn it_should_report_bad_lines() {
+        let (_, errors) = Locale::parse("xx", "score = Score: {score}\nnonsense\n");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn it_should_take_code_from_file_name() {
+        assert_eq!(code_of("locales/de.txt"), Some("de"));
+    }
+}
@@ -1,44 +1,144 @@
+// Lints the code was written against before clippy flagged them
+#![allow(clippy::unused_unit)] // Functions spell out `-> ()`
+#![allow(clippy::bool_assert_comparison)] // Tests compare bools with assert_eq!
+#![allow(clippy::ptr_arg)] // Update functions take &mut Vec
+#![allow(clippy::bool_comparison)] // Tween::is_finished
+#![allow(clippy::useless_conversion)] // Tween tests zip with into_iter
+#![allow(clippy::match_like_matches_macro)] // Player::is_active
+#![allow(clippy::needless_late_init)] // Offscreen pointer placement
+#![allow(clippy::unnecessary_cast)] // UI stat bars
+
 extern crate find_folder;
 extern crate fps_counter;
 extern crate piston_window;
 extern crate rand;
 extern crate sprite;
 
+mod animation;
+mod assets;
+mod atlas;
 mod background;
+mod camera;
+mod config;
+mod controls;
+mod fonts;
 mod game;
+mod gamepad;
+mod highscores;
+mod input;
+mod layout;
+mod locale;
 mod missile;
 mod offscreen;
+mod options;
+mod particles;
 mod pickups;
 mod player;
+mod radar;
+mod scan;
 mod settings;
+mod ship;
+mod spatial;
+mod stats;
 mod traits;
 mod tween;
 mod ui;
+mod viewport;
 
+use animation::{Animation, Clips};
+use assets::{AssetKind, AssetManifest};
+use atlas::Atlas;
 use background::*;
+use camera::Camera;
+use config::Config;
+use controls::GameAction;
+use fonts::Fonts;
 use game::*;
+use gamepad::{Gamepads, PistonBackend};
+use highscores::HighScores;
+use locale::Locale;
 use missile::*;
+use options::Options;
+use particles::Particles;
 use pickups::*;
 use piston_window::*;
 use player::*;
+use scan::Scanner;
+use ship::ShipSelect;
+use stats::{PersonalBests, RunStats};
 use tween::*;
+use ui::Canvas;
+use viewport::Viewport;
 
 fn main() {
     let (width, height) = settings::window::SIZE;
-    let centre = Point::new(width as f64 / 2.0, height as f64 / 2.0);
     let opengl = OpenGL::V3_2;
     let mut window: PistonWindow = WindowSettings::new("Iron Sky", (width, height))
-        .exit_on_esc(true)
+        .resizable(true)
         .opengl(opengl)
         .build()
         .unwrap();
 
+    // Everything is drawn on the logical viewport, which is scaled to fit the window
+    let mut viewport = Viewport::new(width as f64, height as f64);
+
     let assets = find_folder::Search::ParentsThenKids(3, 3)
         .for_folder("assets")
         .unwrap();
 
     window.set_ups_reset(0);
 
+    // Check every asset up front, so all the problems can be fixed in one go
+    let default_locale = locale::file(settings::locale::DEFAULT);
+    let used_files = [
+        (AssetKind::Sprite, settings::missile::FILE),
+        (AssetKind::Sprite, settings::offscreen_pointer::FILE),
+        (AssetKind::Font, settings::ui::FONT),
+        (AssetKind::Locale, &default_locale),
+    ];
+    let mut used_clips = vec![
+        settings::player::EXPLOSION_CLIP,
+        settings::missile::EXPLOSION_CLIP,
+        settings::pickup::CLIP,
+    ];
+    for ship in ship::ROSTER.iter() {
+        used_clips.extend_from_slice(&ship.clips);
+    }
+    let report = assets::validate(&assets, &used_files, &used_clips);
+    for problem in report.problems.iter() {
+        warn("Assets", problem);
+    }
+    if !report.problems.is_empty() {
+        // Development builds carry on with placeholders for anything missing
+        if report.fatal || !cfg!(debug_assertions) {
+            ::std::process::exit(1);
+        }
+        warn("Assets", "using placeholder textures");
+    }
+
+    // Sprites
+    let manifest = AssetManifest::load(&assets);
+    let sprite_files = manifest.files(AssetKind::Sprite);
+    let atlas = Atlas::build(&mut window, &assets, &sprite_files);
+
+    // Animation clips
+    let clips = match Clips::load(&mut window, &assets, settings::animation::MANIFEST, &atlas) {
+        Ok(clips) => clips,
+        Err(_) => ::std::process::exit(1), // Already reported by the validation pass
+    };
+
+    // Config
+    let (mut config, config_errors) =
+        Config::load(::std::path::PathBuf::from(settings::config::FILE));
+    for err in config_errors {
+        warn("Config", err);
+    }
+    let mut options = Options::new(locale::available(&assets, &manifest));
+
+    // Debugging
+    let mut draw_debug = settings::game::DRAW_DEBUG;
+    let mut paused = false;
+
     // FPS counter
     let mut fps_counter = fps_counter::FPSCounter::new();
     let mut ups_counter = fps_counter::FPSCounter::new();
@@ -61,64 +161,106 @@ fn main() {
     );
     score_ticker.reset();
 
+    // High scores
+    let mut high_scores = HighScores::load(::std::path::PathBuf::from(
+        settings::high_score::FILE,
+    ));
+    let mut score_submitted = false;
+
+    // Stats for the game over screen
+    let mut run_stats = RunStats::new();
+    let mut personal_bests =
+        PersonalBests::load(::std::path::PathBuf::from(settings::stats::FILE));
+    let mut stat_lines = vec![];
+
+    // Ship selection
+    let mut screen = Screen::ShipSelect;
+    let mut ship_select = ShipSelect::new();
+
     // Player
-    let mut tex_explosion_player = AnimTexture::new(&mut window, &assets, "explosions/2.png", 8, 8);
-    let mut spr_player = initialise_player_sprites(
-        &mut window,
-        &assets,
-        ["playerLeft.png", "player.png", "playerRight.png"],
-        settings::player::SCALE,
-    );
     let mut player = Player::new(
-        Collider::new(centre, settings::player::COLLIDER_RADIUS),
+        ship_select.ship(),
+        ship_clips(ship_select.ship(), &clips),
+        Collider::with_shape(Point::new(0.0, 0.0), ship_select.ship().hitbox),
         Animation::new(
-            settings::player::EXPLOSION_LENGTH,
+            clips.get(settings::player::EXPLOSION_CLIP),
             settings::player::EXPLOSION_SCALE,
         ),
     );
 
+    // Camera
+    let mut camera = Camera::new(player.collider.pos, viewport);
+    let mut hit_stop_frames = 0;
+
     // Missiles
-    let mut spr_missile = load_sprite(
-        &mut window,
-        &assets,
-        "missile.png",
-        settings::missile::SCALE,
-    );
-    let mut tex_explosion_missile =
-        AnimTexture::new(&mut window, &assets, "explosions/4.png", 8, 8);
+    let mut spr_missile = atlas.sprite(settings::missile::FILE, settings::missile::SCALE);
 
-    let mut missiles = initialise_missiles();
+    let mut missiles = initialise_missiles(&clips);
     let mut missile_gen = missile::Generator::new();
-    missile_gen.reset_missiles(&mut missiles);
+    missile_gen.reset_missiles(&mut missiles, &player);
 
     // Pickups
-    let mut spr_pickup = load_sprite(&mut window, &assets, "star.png", settings::pickup::SCALE);
-
-    let mut pickups = initialise_pickups();
+    let mut pickups = initialise_pickups(&clips);
     let mut pickup_gen = pickups::Generator::new();
     pickup_gen.reset_pickups(&mut pickups);
 
+    // Particles
+    let mut particles = Particles::new(settings::particles::BUDGET);
+
     // Offscreen Pointer
-    let mut spr_pointer = load_sprite(
-        &mut window,
-        &assets,
-        "offscreen_pointer.png",
+    let mut spr_pointer = atlas.sprite(
+        settings::offscreen_pointer::FILE,
         settings::offscreen_pointer::SCALE,
     );
 
     // Background
-    let mut background = Background::new(&mut window, &assets, settings::background::FILES);
+    let mut scenery = Scenery::new(&mut window, &assets, &load_themes(&assets));
+    let theme = scenery.theme_at(config.game_speed, 0.0);
+    scenery.snap_to_theme(theme);
+    let mut sky = Background::procedural(sky_seed(config.background_seed));
 
-    // Key State
-    let mut left_key = KeyState::NotPressed;
-    let mut right_key = KeyState::NotPressed;
+    // Input
+    let mut input = input::new_input_source(config.input_mode, config.hold_turns_right);
+    let mut gamepads = Gamepads::new(Box::new(PistonBackend::new()));
 
-    // UI
-    let mut ui = ui::UI::new();
+    // Menus step through their choices by themselves for one-button players
+    let mut scanner = Scanner::new();
+    let mut scanned_menu = (screen, player.is_active());
 
-    // Fonts
-    let font = &assets.join("fonts/Gugi-Regular.ttf");
-    let mut glyphs = Glyphs::new(font, window.factory.clone(), TextureSettings::new()).unwrap();
+    // UI
+    let mut ui = ui::UI::new(&clips);
+
+    // Text and the fonts it needs
+    let (mut locale, mut fonts) = match load_locale(&mut window, &assets, &config.locale) {
+        Some(loaded) => loaded,
+        None => ::std::process::exit(1),
+    };
+
+    // Fallback fonts are picked by the locale, so the two are loaded together
+    fn load_locale(
+        window: &mut PistonWindow,
+        assets: &::std::path::Path,
+        code: &str,
+    ) -> Option<(Locale, Fonts)> {
+        let (locale, errors) = Locale::load(assets, code);
+        for err in errors {
+            warn("Locale", err);
+        }
+        let mut files = vec![settings::ui::FONT];
+        files.extend(locale.fonts());
+        match Fonts::load(window, assets, &files) {
+            Ok((fonts, errors)) => {
+                for err in errors {
+                    warn("Fonts", err);
+                }
+                Some((locale, fonts))
+            }
+            Err(err) => {
+                warn("Fonts", err);
+                None
+            }
+        }
+    }
 
     fn get_score_in_tens(tw: &Tween) -> Score {
         (tw.get_val() / 10.0).floor() as u32 * 10 as Score
@@ -126,124 +268,409 @@ fn main() {
 
     while let Some(e) = window.next() {
         // Render loop
-        window.draw_2d(&e, |c, g| {
+        window.draw_2d(&e, |window_c, g| {
             clear([1.0; 4], g); // Clear to white
 
-            // Render objects in background first
-            background.draw(height, width, c, g);
+            let c = viewport.transform(window_c);
 
-            for pickup in &mut pickups {
-                pickup.draw(&mut spr_pickup, &mut spr_pointer, c, g);
+            // Render objects in background first
+            if config.procedural_background {
+                sky.draw(&camera, &viewport, c, g);
+            } else {
+                scenery.draw(&camera, &viewport, c, g);
             }
-            for missile in &mut missiles {
-                missile.draw(
-                    &mut spr_missile,
-                    &mut tex_explosion_missile,
-                    &mut spr_pointer,
-                    c,
-                    g,
-                );
-            }
-            player.draw(&mut spr_player, &mut tex_explosion_player, c, g);
-
-            // Debugging
-            if settings::game::DRAW_DEBUG {
-                // Draw collision shapes
-                player.collider.draw_debug(c, g);
-                for missile in &mut missiles {
-                    missile.collider.draw_debug(c, g);
+
+            let scan = if config.input_mode.is_one_button() {
+                Some(scanner.choice())
+            } else {
+                None
+            };
+            match screen {
+                Screen::ShipSelect => {
+                    let canvas = &mut Canvas::new(&locale, &mut fonts, &clips, &viewport, c);
+                    let entries = high_scores.entries(config.game_speed);
+                    ui.draw_ship_select(ship_select.ship(), &entries, &config, scan, canvas, g);
                 }
-                for pickup in &mut pickups {
-                    pickup.collider.draw_debug(c, g);
+                Screen::Options => {
+                    let canvas = &mut Canvas::new(&locale, &mut fonts, &clips, &viewport, c);
+                    ui.draw_options(&options, &config, canvas, g);
                 }
-                // UPS Counter
-                let transform = c.transform.trans(5.0, 25.0);
-                text::Text::new_color([1.0, 0.0, 0.0, 1.0], 16)
-                    .round()
-                    .draw(
-                        &("ups: ".to_owned() + &ups.to_string()),
-                        &mut glyphs,
-                        &c.draw_state,
-                        transform,
-                        g,
-                    )
-                    .unwrap();
-                // FPS Counter
-                let transform = c.transform.trans(5.0, 50.0);
-                text::Text::new_color([1.0, 0.0, 0.0, 1.0], 16)
-                    .round()
-                    .draw(
-                        &("fps: ".to_owned() + &fps_counter.tick().to_string()),
-                        &mut glyphs,
-                        &c.draw_state,
-                        transform,
+                Screen::Playing => {
+                    particles.draw(camera.transform(c), g);
+                    for pickup in &mut pickups {
+                        pickup.draw(&clips, &mut spr_pointer, &camera, c, g);
+                    }
+                    for missile in &mut missiles {
+                        missile.draw(&mut spr_missile, &clips, &mut spr_pointer, &camera, c, g);
+                    }
+                    let world = camera.transform(c);
+                    player.draw(&clips, world, g);
+
+                    // Debugging
+                    if draw_debug {
+                        // Draw collision shapes
+                        player.collider.draw_debug(world, g);
+                        for missile in &mut missiles {
+                            missile.collider.draw_debug(world, g);
+                        }
+                        for pickup in &mut pickups {
+                            pickup.collider.draw_debug(world, g);
+                        }
+                        // UPS Counter
+                        let transform = c.transform.trans(5.0, 25.0);
+                        fonts.draw(
+                            &("ups: ".to_owned() + &ups.to_string()),
+                            [1.0, 0.0, 0.0, 1.0],
+                            16,
+                            transform,
+                            c,
+                            g,
+                        );
+                        // FPS Counter
+                        let transform = c.transform.trans(5.0, 50.0);
+                        fonts.draw(
+                            &("fps: ".to_owned() + &fps_counter.tick().to_string()),
+                            [1.0, 0.0, 0.0, 1.0],
+                            16,
+                            transform,
+                            c,
+                            g,
+                        );
+                        // Particle Counter
+                        let transform = c.transform.trans(5.0, 75.0);
+                        fonts.draw(
+                            &("particles: ".to_owned() + &particles.count().to_string()),
+                            [1.0, 0.0, 0.0, 1.0],
+                            16,
+                            transform,
+                            c,
+                            g,
+                        );
+                    }
+
+                    // Draw UI
+                    ui.draw(
+                        score + get_score_in_tens(&score_ticker),
+                        &stat_lines,
+                        &config.bindings,
+                        scan.filter(|_| !player.is_active()),
+                        &mut Canvas::new(&locale, &mut fonts, &clips, &viewport, c),
                         g,
-                    )
-                    .unwrap();
+                    );
+                    radar::draw_radar(&player, &missiles, &pickups, &viewport, c, g);
+
+                    // Time until each missile hits and each star disappears
+                    if config.pointer_labels {
+                        use offscreen::draw_offscreen_label;
+
+                        if player.is_active() {
+                            for missile in &missiles {
+                                let time = missile
+                                    .time_to_impact(player.collider.pos, player.velocity());
+                                if let Some(time) = time {
+                                    draw_offscreen_label(
+                                        &locale.format(
+                                            "pointer.seconds",
+                                            &[("time", &locale.decimal(time, 1))],
+                                        ),
+                                        camera.to_screen(missile.collider.pos),
+                                        settings::missile::POINTER_COLOR,
+                                        &viewport,
+                                        &mut fonts,
+                                        c,
+                                        g,
+                                    );
+                                }
+                            }
+                        }
+                        for pickup in &pickups {
+                            if let Some(time) = pickup.time_left() {
+                                draw_offscreen_label(
+                                    &locale.format(
+                                        "pointer.seconds",
+                                        &[("time", &locale.decimal(time.ceil(), 0))],
+                                    ),
+                                    camera.to_screen(pickup.collider.pos),
+                                    settings::pickup::POINTER_COLOR,
+                                    &viewport,
+                                    &mut fonts,
+                                    c,
+                                    g,
+                                );
+                            }
+                        }
+                    }
+                    if paused {
+                        let canvas = &mut Canvas::new(&locale, &mut fonts, &clips, &viewport, c);
+                        ui.draw_paused(canvas, g);
+                    }
+                }
             }
 
-            // Draw UI
-            ui.draw(score + get_score_in_tens(&score_ticker), &mut glyphs, c, g);
+            viewport.draw_letterbox(window_c, g);
         });
 
+        if let Some([w, h]) = e.resize_args() {
+            viewport.resize(w as f64, h as f64);
+            camera.set_viewport(viewport);
+        }
+
         // Input loop
+        input.event(&e);
+        gamepads.event(&e);
+
+        // Keyboard and gamepads both feed the same actions
+        let connected_pads = gamepads.connected();
+        let mut actions = gamepads.poll();
+        input.stick(gamepads.stick());
+
+        // Pause if a gamepad is unplugged mid-game
+        if gamepads.connected() < connected_pads && screen == Screen::Playing {
+            paused = player.is_active();
+        }
+
         if let Some(press_args) = e.press_args() {
-            match press_args {
-                Button::Keyboard(Key::Left) => left_key = KeyState::Pressed,
-                Button::Keyboard(Key::Right) => right_key = KeyState::Pressed,
-                Button::Keyboard(Key::Space) => {
-                    missile_gen.reset_missiles(&mut missiles);
-                    pickup_gen.reset_pickups(&mut pickups);
-                    player.reset();
-                    score_ticker.reset();
-                    score = 0;
+            match (screen, press_args) {
+                (Screen::Options, Button::Keyboard(key)) if options.captures(key, &config) => {
+                    options.key_pressed(key, &mut config);
+                }
+                _ => {
+                    if let Some(action) = config.bindings.action(press_args) {
+                        actions.push((action, true));
+                    }
                 }
-                _ => (),
             }
-            player.input(left_key, right_key);
         }
 
         if let Some(release_args) = e.release_args() {
-            match release_args {
-                Button::Keyboard(Key::Left) => left_key = KeyState::NotPressed,
-                Button::Keyboard(Key::Right) => right_key = KeyState::NotPressed,
-                _ => (),
+            if let Some(action) = config.bindings.action(release_args) {
+                actions.push((action, false));
+            }
+        }
+
+        let scanning = config.input_mode.is_one_button();
+        for (action, pressed) in actions {
+            input.action(action, pressed);
+            if !pressed {
+                continue;
+            }
+
+            let mut restart = false;
+            match screen {
+                Screen::ShipSelect => match action {
+                    GameAction::TurnLeft => ship_select.prev(),
+                    GameAction::TurnRight => ship_select.next(),
+                    GameAction::Restart if scanning && scanner.choice() == ship::ROSTER.len() => {
+                        screen = Screen::Options
+                    }
+                    GameAction::Restart => {
+                        player.set_ship(ship_select.ship(), ship_clips(ship_select.ship(), &clips));
+                        screen = Screen::Playing;
+                        restart = true;
+                    }
+                    GameAction::Options => screen = Screen::Options,
+                    GameAction::Quit => window.set_should_close(true),
+                    _ => (),
+                },
+                Screen::Options => match action {
+                    GameAction::Options | GameAction::Quit => options.close(),
+                    GameAction::Restart if scanning => {
+                        options.key_pressed(Key::Return, &mut config)
+                    }
+                    _ => (),
+                },
+                Screen::Playing => match action {
+                    GameAction::Restart => {
+                        // One-switch steering uses the button mid-run, but it still restarts
+                        // once the run is over, or changes ship if the menu has stepped to that
+                        if !player.is_active() && scanning && scanner.choice() == 1 {
+                            screen = Screen::ShipSelect;
+                        } else if !player.is_active() || !input.captures(action) {
+                            restart = true;
+                        }
+                    }
+                    GameAction::ChangeShip if !player.is_active() => screen = Screen::ShipSelect,
+                    GameAction::Pause => paused = !paused && player.is_active(),
+                    GameAction::Boost if !paused => player.boost(),
+                    GameAction::Debug => draw_debug = !draw_debug,
+                    GameAction::Quit => window.set_should_close(true),
+                    _ => (),
+                },
+            }
+            if restart {
+                input.reset();
+                missile_gen.reset_missiles(&mut missiles, &player);
+                pickup_gen.reset_pickups(&mut pickups);
+                player.reset();
+                camera.snap_to(player.collider.pos);
+                hit_stop_frames = 0;
+                particles.clear();
+                run_stats = RunStats::new();
+                stat_lines.clear();
+                let theme = scenery.theme_at(config.game_speed, 0.0);
+                scenery.snap_to_theme(theme);
+                if config.background_seed.is_none() {
+                    sky = Background::procedural(sky_seed(None));
+                }
+                score_ticker.reset();
+                score = 0;
+                score_submitted = false;
+                paused = false;
             }
-            player.input(left_key, right_key);
+        }
+
+        if config.locale != locale.code() {
+            match load_locale(&mut window, &assets, &config.locale) {
+                Some((new_locale, new_fonts)) => {
+                    locale = new_locale;
+                    fonts = new_fonts;
+                }
+                // Keep the old text rather than trying again every frame
+                None => config.locale = locale.code().to_owned(),
+            }
+        }
+
+        if options.take_closed() {
+            config.save().unwrap_or_else(|err| {
+                warn("Config", format!("could not save: {}", err));
+            });
+            input = input::new_input_source(config.input_mode, config.hold_turns_right);
+            screen = Screen::ShipSelect;
         }
 
         // Update loop
         if let Some(u) = e.update_args() {
-            if settings::game::DRAW_DEBUG {
+            if draw_debug {
                 ups = ups_counter.tick();
             }
 
-            player.update(u.dt);
+            // Stars keep twinkling and themes keep fading behind the menus
+            sky.update(u.dt);
+            scenery.update(u.dt);
+
+            if config.input_mode.is_one_button() {
+                // Start from the first choice whenever a different menu comes up
+                let menu = (screen, player.is_active());
+                let moved = if menu != scanned_menu {
+                    scanner.reset();
+                    scanned_menu = menu;
+                    true
+                } else {
+                    let choices = match screen {
+                        Screen::ShipSelect => ship::ROSTER.len() + 1,
+                        Screen::Options => options.scan_count(),
+                        Screen::Playing if !player.is_active() => 2,
+                        Screen::Playing => 0,
+                    };
+                    scanner.update(u.dt, choices)
+                };
+                if moved {
+                    match screen {
+                        Screen::ShipSelect if scanner.choice() < ship::ROSTER.len() => {
+                            ship_select.select(scanner.choice())
+                        }
+                        Screen::Options => options.scan_to(scanner.choice()),
+                        _ => (),
+                    }
+                }
+            }
+
+            if screen != Screen::Playing || paused {
+                continue;
+            }
+
+            // Game speed slows the simulation but not the UI
+            let dt = u.dt * config.time_scale();
+
+            // The camera keeps shaking while the world is frozen
+            camera.update_shake(dt);
+            if hit_stop_frames > 0 {
+                hit_stop_frames -= 1;
+                continue;
+            }
+
+            input.update(dt);
+            let player_on_screen = camera.to_screen(player.collider.pos);
+            player.steer(input.steering(viewport.to_window(player_on_screen)));
+            player.update(dt);
             for missile in &mut missiles {
-                missile.update(&player, u.dt);
+                missile.update(&player, dt);
             }
             for pickup in &mut pickups {
-                pickup.update(&player, u.dt);
+                pickup.update(dt);
+            }
+
+            if player.is_active() {
+                camera.update(player.collider.pos, player.velocity(), dt);
+            } else {
+                camera.update(player.collider.pos, Point::new(0.0, 0.0), dt);
+            }
+            particles.update(dt);
+            player.emit_particles(&mut particles, dt);
+            for missile in &mut missiles {
+                missile.emit_particles(&mut particles, dt);
             }
 
-            background.update(&player, u.dt);
-            missile_gen.update(&mut missiles, &player, u.dt);
-            pickup_gen.update(&mut pickups, &player, u.dt);
+            missile_gen.update(&mut missiles, &player, dt);
+            pickup_gen.update(&mut pickups, &player, dt);
             ui.update(&player, u.dt);
 
+            let was_active = player.is_active();
             let missile_explosion_count = explosion_collisions(&mut player, &mut missiles);
             let pickups_collected_count = collect_collisions(&player, &mut pickups);
+            let near_miss_gaps = near_misses(&player, &mut missiles);
+            run_stats.add_hits(&player, missile_explosion_count, pickups_collected_count);
+            run_stats.update(&player, &missiles, &near_miss_gaps, dt);
+            if player.is_active() {
+                let theme = scenery.theme_at(config.game_speed, run_stats.survival_time);
+                scenery.set_theme(theme);
+            }
+
+            if config.screen_shake {
+                use settings::screen_shake::{MISSILE_TRAUMA, PLAYER_TRAUMA};
+
+                camera.add_trauma(missile_explosion_count as f64 * MISSILE_TRAUMA);
+                if was_active && !player.is_active() {
+                    camera.add_trauma(PLAYER_TRAUMA);
+                }
+            }
+            if config.hit_stop && was_active && !player.is_active() {
+                hit_stop_frames = settings::screen_shake::HIT_STOP_FRAMES;
+            }
 
             if player.is_active() {
-                score_ticker.update(u.dt);
+                score_ticker.update(dt);
             }
 
             score += (missile_explosion_count * settings::game::POINTS_PER_MISSILE)
-                + (pickups_collected_count * settings::game::POINTS_PER_PICKUP) as Score;
+                + (pickups_collected_count * settings::game::POINTS_PER_PICKUP)
+                + (near_miss_gaps.len() as u32 * settings::game::POINTS_PER_NEAR_MISS) as Score;
+
+            // Record the final score once the game is over
+            if let State::Inactive = player.state {
+                if !score_submitted {
+                    let final_score = score + get_score_in_tens(&score_ticker);
+                    if high_scores.submit(final_score, player.ship().name, config.game_speed) {
+                        high_scores.save().unwrap_or_else(|err| {
+                            warn("High scores", format!("could not save: {}", err));
+                        });
+                    }
+                    stat_lines = personal_bests.submit(&run_stats);
+                    personal_bests.save().unwrap_or_else(|err| {
+                        warn("Personal bests", format!("could not save: {}", err));
+                    });
+                    score_submitted = true;
+                }
+            }
         }
     }
 }
 
+// Every problem the game can carry on past is reported here, tagged with where it came from
+fn warn<T: ::std::fmt::Display>(source: &str, problem: T) -> () {
+    eprintln!("{}: {}", source, problem);
+}
+
 #[cfg(test)]
 #[macro_use]
 extern crate assert_approx_eq;
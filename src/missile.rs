@@ -1,13 +1,18 @@
 extern crate piston_window;
 
-use game::{Animation, *};
+use animation::{Animation, AnimationEvent, Clips};
+use camera::Camera;
+use game::*;
+use particles::{Emitter, Particles};
 use piston_window::*;
 use player::*;
 use sprite::*;
 use traits::Collides;
+use tween::*;
 
 #[derive(Copy, Clone, PartialEq)]
 enum State {
+    Incoming, // Warning shown before the missile goes active
     Active,
     Exploding,
     Inactive,
@@ -18,6 +23,13 @@ pub struct Missile {
     pub collider: Collider,
     velocity: Point,
     explosion: Animation,
+    warning: Tween,
+    exhaust: Emitter,
+    burst_pending: bool, // Sparks still to be thrown out for the latest explosion
+    urgency: f64, // How close the missile is to the player, from 0 (far) to 1 (near)
+    pulse: f64,   // Phase of the pointer's pulse, in radians
+    passing: Option<f64>, // Closest gap so far while inside the near miss margin
+    flash: Tween,
 }
 
 impl Collides for Missile {
@@ -32,27 +44,64 @@ impl Collides for Missile {
 
 impl Missile {
     pub fn new(mut collider: Collider, velocity: Point, explosion: Animation) -> Missile {
+        use settings::missile::WARNING_LENGTH;
+        use settings::near_miss::FLASH_LENGTH;
+        use settings::particles::MISSILE_EXHAUST;
+
         collider.disable();
         Missile {
             state: State::Inactive,
             collider,
             velocity,
             explosion,
+            // Flashes the pointer a few times before settling
+            warning: Tween::new(
+                vec![
+                    (0.0, 0.0),
+                    (0.15, 1.4),
+                    (0.3, 0.8),
+                    (0.45, 1.4),
+                    (0.6, 0.8),
+                    (0.75, 1.4),
+                    (1.0, 1.0),
+                ],
+                WARNING_LENGTH,
+                Easing::Linear,
+                false,
+            ),
+            exhaust: Emitter::new(&MISSILE_EXHAUST),
+            burst_pending: false,
+            urgency: 0.0,
+            pulse: 0.0,
+            passing: None,
+            flash: Tween::new(
+                vec![(0.0, 0.0), (1.0, 1.0)],
+                FLASH_LENGTH,
+                Easing::EaseOut,
+                false,
+            ),
         }
     }
 
     pub fn update(&mut self, player: &Player, dt: f64) {
         match self.state {
+            State::Incoming => {
+                self.warning.update(dt);
+                if !self.warning.is_playing() {
+                    self.activate();
+                }
+            }
             State::Active => {
-                self.update_position(player, dt);
+                self.update_position(dt);
+                self.update_pulse(player, dt);
+                self.flash.update(dt);
                 if player.is_active() {
                     self.update_velocity(player, dt);
                 }
             }
             State::Exploding => {
-                self.update_position(player, dt);
-                self.update_explosion(dt);
-                if !self.explosion.is_playing() {
+                self.update_position(dt);
+                if let Some(AnimationEvent::Finished) = self.update_explosion(dt) {
                     self.state = State::Inactive;
                 }
             }
@@ -63,72 +112,202 @@ impl Missile {
     pub fn draw(
         &mut self,
         sprite: &mut Sprite<G2dTexture>,
-        explosion_tex: &mut AnimTexture,
+        clips: &Clips,
         pointer: &mut Sprite<G2dTexture>,
+        camera: &Camera,
         c: piston_window::Context,
         g: &mut G2d,
     ) -> () {
-        use offscreen::{draw_anim_offscreen, draw_offscreen};
+        use offscreen::{draw_anim_offscreen, draw_offscreen, place_pointer};
         use settings::missile::POINTER_COLOR;
 
+        let world = camera.transform(c);
+        let placed = place_pointer(camera.to_screen(self.collider.pos), camera.viewport());
+
         match self.state {
+            State::Incoming => {
+                use offscreen::draw_offscreen_pointer;
+                use settings::missile::WARNING_COLOR;
+
+                if let Some(placed) = placed {
+                    let scale = self.warning.get_val();
+                    draw_offscreen_pointer(pointer, placed, WARNING_COLOR, scale, c, g);
+                }
+            }
             State::Active => {
                 let rot = self.get_rotation();
                 sprite.set_position(self.collider.pos.x, self.collider.pos.y);
                 sprite.set_rotation(rot);
-                sprite.draw(c.transform, g);
+                sprite.draw(world.transform, g);
+                if self.flash.is_playing() {
+                    self.draw_flash(world, g);
+                }
 
-                draw_offscreen(sprite, pointer, self.collider.pos, POINTER_COLOR, c, g);
+                if let Some(placed) = placed {
+                    let scale = self.pointer_scale();
+                    draw_offscreen(sprite, pointer, placed, POINTER_COLOR, scale, c, g);
+                }
             }
             State::Exploding => {
-                self.explosion.draw(explosion_tex, c, g);
-
-                draw_anim_offscreen(
-                    &mut self.explosion,
-                    explosion_tex,
-                    pointer,
-                    self.collider.pos,
-                    POINTER_COLOR,
-                    c,
-                    g,
-                );
+                self.explosion.draw(clips, world, g);
+
+                if let Some(placed) = placed {
+                    let explosion = &self.explosion;
+                    draw_anim_offscreen(explosion, clips, pointer, placed, POINTER_COLOR, c, g);
+                }
             }
             State::Inactive => {}
         }
     }
 
+    // Ring spreading out to the edge of the near miss margin as it fades
+    fn draw_flash(&self, c: piston_window::Context, g: &mut G2d) -> () {
+        use settings::missile::COLLIDER;
+        use settings::near_miss::{FLASH_COLOR, FLASH_THICKNESS, MARGIN};
+
+        let progress = self.flash.get_val();
+        let r = COLLIDER.radius() + MARGIN * progress;
+        let mut color = FLASH_COLOR;
+        color[3] *= (1.0 - progress) as f32;
+        let rect = [self.collider.pos.x - r, self.collider.pos.y - r, r * 2.0, r * 2.0];
+        Ellipse::new_border(color, FLASH_THICKNESS).draw(rect, &c.draw_state, c.transform, g);
+    }
+
+    // Tracks the missile going into and back out of the near miss margin, given its gap to the
+    // player while inside. Returns the closest it came once it has made it back out, and flashes
+    // to show it counted.
+    pub fn pass_player(&mut self, gap: Option<f64>) -> Option<f64> {
+        let passed = match gap {
+            Some(gap) => {
+                self.passing = Some(self.passing.map_or(gap, |closest| closest.min(gap)));
+                None
+            }
+            None => self.passing.take(),
+        };
+        if passed.is_some() {
+            self.flash.reset();
+        }
+        passed
+    }
+
     pub fn explode(&mut self) -> () {
         match self.state {
             State::Active => {
                 self.state = State::Exploding;
                 self.collider.disable();
+                self.burst_pending = true;
                 self.explosion.play();
                 self.explosion.set_pos(self.collider.pos);
             }
-            State::Exploding | State::Inactive => {}
+            State::Incoming | State::Exploding | State::Inactive => {}
         }
     }
 
+    // The missile waits at its spawn point, showing a warning, before it goes active
     pub fn place(&mut self, pos: Point, velocity: Point) -> () {
-        self.collider.pos = pos;
+        self.collider.place(pos);
         self.velocity = velocity;
+        self.collider.set_rotation(self.get_rotation());
+        self.state = State::Incoming;
+        self.collider.disable();
+        self.explosion.stop();
+        self.warning.reset();
+        self.flash.stop();
+        self.passing = None;
+    }
+
+    fn activate(&mut self) -> () {
         self.state = State::Active;
         self.collider.enable();
-        self.explosion.stop();
     }
 
     pub fn reset(&mut self) -> () {
         self.state = State::Inactive;
         self.collider.disable();
         self.explosion.stop();
+        self.warning.stop();
+        self.flash.stop();
+        self.passing = None;
+        self.burst_pending = false;
     }
 
-    fn update_position(&mut self, player: &Player, dt: f64) -> () {
-        // Update position (x = x + v*dt)
-        self.collider.pos = self.collider.pos + self.velocity * dt;
+    // Exhaust while flying, and a burst of sparks and debris when it explodes
+    pub fn emit_particles(&mut self, particles: &mut Particles, dt: f64) -> () {
+        use settings::particles::*;
+
+        let no_velocity = Point::new(0.0, 0.0);
+        match self.state {
+            State::Active => {
+                let heading = self.velocity.normalized();
+                let pos = self.collider.pos - heading * MISSILE_EXHAUST_OFFSET;
+                let direction = (-heading.y).atan2(-heading.x);
+                self.exhaust.update(particles, pos, direction, no_velocity, dt);
+            }
+            State::Exploding => {
+                if self.burst_pending {
+                    let pos = self.collider.pos;
+                    particles.emit(&SPARKS, pos, 0.0, no_velocity, MISSILE_SPARKS);
+                    particles.emit(&DEBRIS, pos, 0.0, no_velocity, MISSILE_DEBRIS);
+                    self.burst_pending = false;
+                }
+            }
+            State::Incoming | State::Inactive => {}
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state == State::Active
+    }
+
+    // Whether the missile is flying more or less straight at the target
+    pub fn is_homing_on(&self, target: Point) -> bool {
+        use settings::missile::HOMING_ANGLE;
+
+        if !self.is_active() {
+            return false;
+        }
+        let heading = self.velocity.normalized();
+        let to_target = (target - self.collider.pos).normalized();
+        heading.x * to_target.x + heading.y * to_target.y >= HOMING_ANGLE.to_radians().cos()
+    }
+
+    // Seconds until the missile reaches the target at the speed it is currently closing in
+    pub fn time_to_impact(&self, target: Point, target_velocity: Point) -> Option<f64> {
+        if !self.is_active() {
+            return None;
+        }
+        let to_target = target - self.collider.pos;
+        let relative_velocity = self.velocity - target_velocity;
+        let direction = to_target.normalized();
+        let closing_speed = relative_velocity.x * direction.x + relative_velocity.y * direction.y;
+
+        if closing_speed > 0.0 {
+            Some(to_target.magnitude() / closing_speed)
+        } else {
+            None
+        }
+    }
+
+    fn pointer_scale(&self) -> f64 {
+        use settings::offscreen_pointer::{MAX_SCALE, PULSE_AMOUNT};
+
+        (1.0 + (MAX_SCALE - 1.0) * self.urgency) * (1.0 + PULSE_AMOUNT * self.pulse.sin())
+    }
 
-        // Update position based off player movement
-        self.collider.pos = self.collider.pos - player.velocity() * dt;
+    fn update_pulse(&mut self, player: &Player, dt: f64) -> () {
+        use settings::offscreen_pointer::{MAX_PULSE_RATE, MIN_PULSE_RATE};
+
+        self.urgency = urgency((player.collider.pos - self.collider.pos).magnitude());
+        let rate = MIN_PULSE_RATE + (MAX_PULSE_RATE - MIN_PULSE_RATE) * self.urgency;
+        self.pulse = (self.pulse + rate * dt * ::std::f64::consts::PI * 2.0)
+            % (::std::f64::consts::PI * 2.0);
+    }
+
+    fn update_position(&mut self, dt: f64) -> () {
+        // Update position (x = x + v*dt)
+        let pos = self.collider.pos + self.velocity * dt;
+        self.collider.move_to(pos);
+        self.collider.set_rotation(self.get_rotation());
     }
 
     fn update_velocity(&mut self, player: &Player, dt: f64) -> () {
@@ -141,10 +320,9 @@ impl Missile {
         }
     }
 
-    fn update_explosion(&mut self, dt: f64) -> () {
-        // Update explosion
-        self.explosion.update(dt);
+    fn update_explosion(&mut self, dt: f64) -> Option<AnimationEvent> {
         self.explosion.set_pos(self.collider.pos);
+        self.explosion.update(dt)
     }
 
     fn get_rotation(&self) -> f64 {
@@ -152,16 +330,23 @@ impl Missile {
     }
 }
 
-pub fn initialise_missiles() -> Vec<Missile> {
+fn urgency(distance: f64) -> f64 {
+    use settings::offscreen_pointer::{FAR_DISTANCE, NEAR_DISTANCE};
+
+    ((FAR_DISTANCE - distance) / (FAR_DISTANCE - NEAR_DISTANCE)).clamp(0.0, 1.0)
+}
+
+pub fn initialise_missiles(clips: &Clips) -> Vec<Missile> {
     use settings::{game, missile};
 
     let mut missiles: Vec<Missile> = vec![];
+    let explosion = clips.get(missile::EXPLOSION_CLIP);
 
     for _ in 0..game::MAX_MISSILES {
         let missile = Missile::new(
-            Collider::new(Point::new(0.0, 0.0), missile::COLLIDER_RADIUS),
+            Collider::with_shape(Point::new(0.0, 0.0), missile::COLLIDER),
             Point::new(0.0, 0.0),
-            Animation::new(missile::EXPLOSION_LENGTH, missile::EXPLOSION_SCALE),
+            Animation::new(explosion.clone(), missile::EXPLOSION_SCALE),
         );
 
         missiles.push(missile);
@@ -170,17 +355,16 @@ pub fn initialise_missiles() -> Vec<Missile> {
     missiles
 }
 
-fn place_missile(missile: &mut Missile) -> () {
+fn place_missile(missile: &mut Missile, centre: Point) -> () {
     use rand::{thread_rng, Rng};
-    use settings::{missile_generator, window};
+    use settings::missile_generator;
 
     let mut rng = thread_rng();
     let angle = rng.gen_range(0.0, ::std::f64::consts::PI * 2.0);
 
-    let (width, height) = window::SIZE;
     let pos = Point::new(
-        (width as f64) / 2.0 - (angle.cos() * missile_generator::SPAWN_RADIUS),
-        (height as f64) / 2.0 - (angle.sin() * missile_generator::SPAWN_RADIUS),
+        centre.x - (angle.cos() * missile_generator::SPAWN_RADIUS),
+        centre.y - (angle.sin() * missile_generator::SPAWN_RADIUS),
     );
     let velocity = Point::new(0.0, 0.0);
 
@@ -218,18 +402,108 @@ impl Generator {
 
         if place_new_missile {
             if let Some(idx) = missiles.iter().position(|m| m.state == State::Inactive) {
-                place_missile(&mut missiles[idx]);
+                place_missile(&mut missiles[idx], player.collider.pos);
             }
         }
     }
 
-    pub fn reset_missiles(&mut self, missiles: &mut Vec<Missile>) -> () {
+    pub fn reset_missiles(&mut self, missiles: &mut [Missile], player: &Player) -> () {
         for missile in missiles.iter_mut() {
             missile.reset();
         }
 
-        place_missile(&mut missiles[0]);
+        place_missile(&mut missiles[0], player.collider.pos);
 
         self.time_since_last_missile = 0.0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use animation::{test_clip, PlayMode};
+    use player::test_player;
+    use settings::offscreen_pointer::{FAR_DISTANCE, NEAR_DISTANCE};
+
+    fn flying(pos: Point, velocity: Point) -> Missile {
+        let mut missile = Missile::new(
+            Collider::new(Point::new(0.0, 0.0), 1.0),
+            Point::new(0.0, 0.0),
+            Animation::new(test_clip(&[1.0], PlayMode::Once), 1.0),
+        );
+        missile.place(pos, velocity);
+        missile.activate();
+        missile
+    }
+
+    #[test]
+    fn it_should_estimate_time_to_impact() {
+        let missile = flying(Point::new(-400.0, 0.0), Point::new(300.0, 0.0));
+        let time = missile
+            .time_to_impact(Point::new(0.0, 0.0), Point::new(100.0, 0.0))
+            .unwrap();
+        assert_approx_eq!(time, 2.0);
+    }
+
+    #[test]
+    fn it_should_not_estimate_impact_when_moving_away() {
+        let missile = flying(Point::new(-400.0, 0.0), Point::new(-300.0, 0.0));
+        assert!(
+            missile
+                .time_to_impact(Point::new(0.0, 0.0), Point::new(0.0, 0.0))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn it_should_warn_before_going_active() {
+        use settings::missile::WARNING_LENGTH;
+
+        let player = test_player();
+        let mut missile = flying(Point::new(0.0, 0.0), Point::new(0.0, 0.0));
+        missile.place(Point::new(-1200.0, 0.0), Point::new(0.0, 0.0));
+
+        missile.update(&player, WARNING_LENGTH / 2.0);
+        assert!(missile.state == State::Incoming);
+        assert!(!missile.is_active());
+
+        missile.update(&player, WARNING_LENGTH);
+        assert!(missile.is_active());
+    }
+
+    #[test]
+    fn it_should_score_near_miss_once_per_pass() {
+        use game::{explosion_collisions, near_misses};
+        use settings::near_miss::MARGIN;
+
+        let mut player = test_player();
+        player.reset();
+        let near = Point::new(0.0, 2.0 + MARGIN / 2.0);
+        let far = Point::new(0.0, 2.0 + MARGIN * 2.0);
+        let mut missiles = vec![flying(near, Point::new(0.0, 0.0))];
+
+        // Nothing until the missile is clear again, then the closest it came
+        assert_eq!(near_misses(&player, &mut missiles), vec![]);
+        missiles[0].collider.pos = near + Point::new(0.0, 1.0);
+        assert_eq!(near_misses(&player, &mut missiles), vec![]);
+        missiles[0].collider.pos = far;
+        assert_eq!(near_misses(&player, &mut missiles), vec![MARGIN / 2.0]);
+        assert!(missiles[0].flash.is_playing());
+        assert_eq!(near_misses(&player, &mut missiles), vec![]);
+
+        // Coming close then hitting isn't a near miss
+        missiles[0].collider.pos = near;
+        near_misses(&player, &mut missiles);
+        missiles[0].collider.pos = Point::new(0.0, 0.0);
+        explosion_collisions(&mut player, &mut missiles);
+        missiles[0].collider.pos = far;
+        assert_eq!(near_misses(&player, &mut missiles), vec![]);
+    }
+
+    #[test]
+    fn it_should_clamp_urgency() {
+        assert_approx_eq!(urgency(FAR_DISTANCE * 2.0), 0.0);
+        assert_approx_eq!(urgency(NEAR_DISTANCE / 2.0), 1.0);
+        assert_approx_eq!(urgency((FAR_DISTANCE + NEAR_DISTANCE) / 2.0), 0.5);
+    }
+}
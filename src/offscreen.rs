@@ -1,38 +1,71 @@
 extern crate piston_window;
 
+use animation::{Animation, Clips};
+use fonts::Fonts;
 use game::*;
 use piston_window::*;
-use settings::{offscreen_pointer, window};
+use settings::offscreen_pointer;
 use sprite::Sprite;
+use viewport::Viewport;
 
+// The pointer goes where place_pointer puts it. Scale is relative to the pointer's normal
+// size, so closer threats can be drawn bigger.
 pub fn draw_offscreen(
     obj_spr: &mut Sprite<G2dTexture>,
     pointer_spr: &mut Sprite<G2dTexture>,
-    obj_pos: Point,
+    (pos, rot): (Point, f64),
     color: [f32; 3],
+    scale: f64,
     c: piston_window::Context,
     g: &mut G2d,
 ) -> () {
-    if let Some((pos, rot)) = place_pointer(obj_pos) {
-        draw_pointer(pointer_spr, pos, rot, color, c, g);
-        draw_overlay(obj_spr, pos, c, g)
-    }
+    draw_pointer(pointer_spr, pos, rot, color, scale, c, g);
+    draw_overlay(obj_spr, pos, c, g)
+}
+
+// Just the pointer, for objects with nothing to show yet
+pub fn draw_offscreen_pointer(
+    pointer_spr: &mut Sprite<G2dTexture>,
+    (pos, rot): (Point, f64),
+    color: [f32; 3],
+    scale: f64,
+    c: piston_window::Context,
+    g: &mut G2d,
+) -> () {
+    draw_pointer(pointer_spr, pos, rot, color, scale, c, g);
 }
 
 pub fn draw_anim_offscreen(
-    obj_anim: &mut Animation,
-    obj_anim_tex: &mut AnimTexture,
+    obj_anim: &Animation,
+    clips: &Clips,
     pointer_spr: &mut Sprite<G2dTexture>,
-    obj_pos: Point,
+    (pos, rot): (Point, f64),
     color: [f32; 3],
     c: piston_window::Context,
     g: &mut G2d,
 ) -> () {
     use settings::offscreen_pointer::OBJ_SCALE;
 
-    if let Some((pos, rot)) = place_pointer(obj_pos) {
-        draw_pointer(pointer_spr, pos, rot, color, c, g);
-        obj_anim.draw_at_pos(obj_anim_tex, pos, OBJ_SCALE, c, g);
+    draw_pointer(pointer_spr, pos, rot, color, 1.0, c, g);
+    obj_anim.draw_at_pos(clips, pos, OBJ_SCALE, c, g);
+}
+
+// Text next to an object's pointer, on the side facing the middle of the screen
+pub fn draw_offscreen_label(
+    text: &str,
+    obj_pos: Point,
+    color: [f32; 3],
+    viewport: &Viewport,
+    font: &mut Fonts,
+    c: piston_window::Context,
+    g: &mut G2d,
+) -> () {
+    use settings::offscreen_pointer::LABEL_FONT_SIZE;
+
+    if let Some(pos) = place_label(obj_pos, viewport) {
+        let color = [color[0], color[1], color[2], 1.0];
+        let transform = c.transform.trans(pos.x, pos.y);
+        font.draw(text, color, LABEL_FONT_SIZE, transform, c, g);
     }
 }
 
@@ -41,13 +74,20 @@ fn draw_pointer(
     pos: Point,
     rot: f64,
     color: [f32; 3],
+    scale: f64,
     c: piston_window::Context,
     g: &mut G2d,
 ) -> () {
+    let (x_scale, y_scale) = sprite.get_scale();
+
+    sprite.set_scale(x_scale * scale, y_scale * scale);
     sprite.set_position(pos.x, pos.y);
     sprite.set_rotation(rot);
     sprite.set_color(color[0], color[1], color[2]);
     sprite.draw(c.transform, g);
+
+    // Set scale back to original
+    sprite.set_scale(x_scale, y_scale);
 }
 
 fn draw_overlay(
@@ -68,23 +108,30 @@ fn draw_overlay(
     sprite.set_scale(x_scale, y_scale);
 }
 
-fn is_offscreen(pos: Point) -> bool {
-    let (screen_x, screen_y) = window::SIZE;
-    let (screen_x, screen_y) = (screen_x as f64, screen_y as f64);
+fn place_label(obj_pos: Point, viewport: &Viewport) -> Option<Point> {
+    use settings::offscreen_pointer::LABEL_OFFSET;
+
+    place_pointer(obj_pos, viewport)
+        .map(|(pos, _)| pos + (viewport.centre() - pos).normalized() * LABEL_OFFSET)
+}
+
+fn is_offscreen(pos: Point, viewport: &Viewport) -> bool {
+    let (screen_x, screen_y) = (viewport.width(), viewport.height());
 
     pos.x <= 0.0 || pos.y <= 0.0 || pos.x >= screen_x || pos.y >= screen_y
 }
 
-fn place_pointer(obj_pos: Point) -> Option<(Point, f64)> {
-    if !is_offscreen(obj_pos) {
+// Where on the edge of the screen to point at an object, and which way, if it's off screen
+pub fn place_pointer(obj_pos: Point, viewport: &Viewport) -> Option<(Point, f64)> {
+    if !is_offscreen(obj_pos, viewport) {
         return None;
     }
 
     let offset = offscreen_pointer::OFFSET;
-    let (screen_x, screen_y) = window::SIZE;
-    let (screen_x, screen_y) = (screen_x as f64, screen_y as f64);
+    let (screen_x, screen_y) = (viewport.width(), viewport.height());
 
-    // Shift coordinates so that player is at (0,0)
+    // Shift coordinates so the centre of the screen is at (0,0). That's where the camera is
+    // looking, which leads the player's ship rather than sitting on it.
     let pos = obj_pos - Point::new(screen_x / 2.0, screen_y / 2.0);
 
     let (min_x, min_y) = (-screen_x / 2.0 + offset, -screen_y / 2.0 + offset);
@@ -94,16 +141,16 @@ fn place_pointer(obj_pos: Point) -> Option<(Point, f64)> {
     if pos.x == 0.0 {
         if obj_pos.y <= 0.0 {
             // Directly above
-            return Some((Point::new(0.0, offset), 0.0));
+            return Some((Point::new(screen_x / 2.0, offset), 0.0));
         }
         if obj_pos.y >= screen_y {
             // Directly below
-            return Some((Point::new(0.0, screen_y - offset), 180.0));
+            return Some((Point::new(screen_x / 2.0, screen_y - offset), 180.0));
         }
         return None;
     }
 
-    // Find gradient of line between player and object point
+    // Find gradient of line between the centre of the screen and object point
     let m = pos.y / pos.x;
 
     // First check what coords would be if you only considered going out of bounds on left or right side
@@ -148,25 +195,61 @@ mod tests {
 
     #[test]
     fn it_should_be_offscreen() {
-        let (x, y) = window::SIZE;
-        let (x, y) = (x as f64, y as f64);
+        let viewport = Viewport::new(1440.0, 720.0);
+        let (x, y) = (viewport.width(), viewport.height());
         let pos1 = Point::new(x + 1.0, y / 2.0);
         let pos2 = Point::new(x / 2.0, y + 1.0);
         let pos3 = Point::new(-1.0, y / 2.0);
         let pos4 = Point::new(x / 2.0, -1.0);
 
-        assert_eq!(is_offscreen(pos1), true);
-        assert_eq!(is_offscreen(pos2), true);
-        assert_eq!(is_offscreen(pos3), true);
-        assert_eq!(is_offscreen(pos4), true);
+        assert!(is_offscreen(pos1, &viewport));
+        assert!(is_offscreen(pos2, &viewport));
+        assert!(is_offscreen(pos3, &viewport));
+        assert!(is_offscreen(pos4, &viewport));
     }
 
     #[test]
     fn it_should_not_be_offscreen() {
-        let (x, y) = window::SIZE;
-        let (x, y) = (x as f64, y as f64);
+        let viewport = Viewport::new(1440.0, 720.0);
+        let (x, y) = (viewport.width(), viewport.height());
         let pos1 = Point::new(x / 2.0, y / 2.0);
 
-        assert_eq!(is_offscreen(pos1), false);
+        assert!(!is_offscreen(pos1, &viewport));
+    }
+
+    #[test]
+    fn it_should_place_label_inside_pointer() {
+        use settings::offscreen_pointer::{LABEL_OFFSET, OFFSET};
+
+        let viewport = Viewport::new(1440.0, 720.0);
+        let (x, y) = (viewport.width(), viewport.height());
+
+        let label = place_label(Point::new(x + 100.0, y / 2.0), &viewport).unwrap();
+        assert_approx_eq!(label.x, x - OFFSET - LABEL_OFFSET);
+        assert_approx_eq!(label.y, y / 2.0);
+        assert!(place_label(Point::new(x / 2.0, y / 2.0), &viewport).is_none());
+    }
+
+    #[test]
+    fn it_should_point_straight_up_from_screen_centre() {
+        use settings::offscreen_pointer::OFFSET;
+
+        let viewport = Viewport::new(1440.0, 720.0);
+        let (x, y) = (viewport.width(), viewport.height());
+
+        let (pos, rot) = place_pointer(Point::new(x / 2.0, -50.0), &viewport).unwrap();
+        assert_eq!(pos, Point::new(x / 2.0, OFFSET));
+        assert_approx_eq!(rot, 0.0);
+        let (pos, _) = place_pointer(Point::new(x / 2.0, y + 50.0), &viewport).unwrap();
+        assert_eq!(pos, Point::new(x / 2.0, y - OFFSET));
+    }
+
+    #[test]
+    fn it_should_use_current_viewport_size() {
+        let viewport = Viewport::new(960.0, 720.0);
+        let pos = Point::new(1200.0, 360.0);
+
+        assert!(is_offscreen(pos, &viewport));
+        assert!(!is_offscreen(pos, &Viewport::new(1680.0, 720.0)));
     }
 }
@@ -0,0 +1,348 @@
+extern crate piston_window;
+
+use config::{switch_name, Config};
+use controls::{key_label, BindingError, GameAction, ACTIONS};
+use locale::Locale;
+use piston_window::*;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum OptionItem {
+    InputMode,
+    HoldDirection,
+    GameSpeed,
+    ScreenShake,
+    HitStop,
+    PointerLabels,
+    ProceduralBackground,
+    Language,
+    Binding(GameAction),
+    Back,
+}
+
+pub struct Options {
+    items: Vec<OptionItem>,
+    selected: usize,
+    rebinding: bool,
+    closed: bool,
+    message: Option<BindingError>,
+    locales: Vec<(String, String)>, // (code, name)
+}
+
+impl Options {
+    pub fn new(locales: Vec<(String, String)>) -> Options {
+        Options {
+            items: vec![
+                OptionItem::InputMode,
+                OptionItem::HoldDirection,
+                OptionItem::GameSpeed,
+                OptionItem::ScreenShake,
+                OptionItem::HitStop,
+                OptionItem::PointerLabels,
+                OptionItem::ProceduralBackground,
+                OptionItem::Language,
+            ].into_iter()
+                .chain(ACTIONS.iter().map(|&a| OptionItem::Binding(a)))
+                .chain(Some(OptionItem::Back))
+                .collect(),
+            selected: 0,
+            rebinding: false,
+            closed: false,
+            message: None,
+            locales,
+        }
+    }
+
+    pub fn key_pressed(&mut self, key: Key, config: &mut Config) -> () {
+        if self.rebinding {
+            self.rebind(key, config);
+            return;
+        }
+
+        match key {
+            Key::Up => self.selected = (self.selected + self.items.len() - 1) % self.items.len(),
+            Key::Down => self.selected = (self.selected + 1) % self.items.len(),
+            Key::Return | Key::Left | Key::Right => match self.items[self.selected] {
+                OptionItem::InputMode => config.input_mode = config.input_mode.next(),
+                OptionItem::HoldDirection => config.hold_turns_right = !config.hold_turns_right,
+                OptionItem::GameSpeed => {
+                    config.game_speed = step_game_speed(config.game_speed, key)
+                }
+                OptionItem::ScreenShake => config.screen_shake = !config.screen_shake,
+                OptionItem::HitStop => config.hit_stop = !config.hit_stop,
+                OptionItem::PointerLabels => config.pointer_labels = !config.pointer_labels,
+                OptionItem::ProceduralBackground => {
+                    config.procedural_background = !config.procedural_background
+                }
+                OptionItem::Language => config.locale = self.next_locale(&config.locale),
+                OptionItem::Binding(_) => {
+                    if key == Key::Return {
+                        self.rebinding = true;
+                        self.message = None;
+                    }
+                }
+                OptionItem::Back => {
+                    if key == Key::Return {
+                        self.closed = true;
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+
+    pub fn is_rebinding(&self) -> bool {
+        self.rebinding
+    }
+
+    // Whether the options screen handles this key itself rather than passing it on as an
+    // action. Everything but the keys for leaving goes to the screen, and while waiting for a
+    // new binding every key does. One-button players change options with their button.
+    pub fn captures(&self, key: Key, config: &Config) -> bool {
+        match config.bindings.action(Button::Keyboard(key)) {
+            _ if self.rebinding => true,
+            Some(GameAction::Options) | Some(GameAction::Quit) => false,
+            Some(GameAction::Restart) => !config.input_mode.is_one_button(),
+            _ => true,
+        }
+    }
+
+    pub fn close(&mut self) -> () {
+        self.closed = true;
+    }
+
+    // Whether the screen was asked to close since the last check
+    pub fn take_closed(&mut self) -> bool {
+        ::std::mem::replace(&mut self.closed, false)
+    }
+
+    // Number of options a one-button player can step through. Bindings are left out as they
+    // need a whole keyboard to change.
+    pub fn scan_count(&self) -> usize {
+        self.scannable().count()
+    }
+
+    pub fn scan_to(&mut self, choice: usize) -> () {
+        let idx = self.scannable().nth(choice);
+        if let Some(idx) = idx {
+            self.selected = idx;
+        }
+    }
+
+    fn scannable<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        self.items.iter().enumerate().filter_map(|(i, item)| match *item {
+            OptionItem::Binding(_) => None,
+            _ => Some(i),
+        })
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn message(&self, locale: &Locale) -> Option<String> {
+        self.message.as_ref().map(|err| match *err {
+            BindingError::Conflict(first, second, key) => locale.format(
+                "options.conflict",
+                &[
+                    ("key", &key_label(key)),
+                    ("first", &action_label(first, locale)),
+                    ("second", &action_label(second, locale)),
+                ],
+            ),
+            _ => err.to_string(),
+        })
+    }
+
+    // (label, value) for each line of the options screen
+    pub fn lines(&self, config: &Config, locale: &Locale) -> Vec<(String, String)> {
+        let switch = |on: bool| locale.get(&format!("options.{}", switch_name(on))).to_owned();
+
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| match *item {
+                OptionItem::InputMode => (
+                    locale.get("options.controls").to_owned(),
+                    locale
+                        .get(&format!("input.{}", config.input_mode.config_name()))
+                        .to_owned(),
+                ),
+                OptionItem::HoldDirection => {
+                    let turn = if config.hold_turns_right {
+                        GameAction::TurnRight
+                    } else {
+                        GameAction::TurnLeft
+                    };
+                    (
+                        locale.get("options.hold_direction").to_owned(),
+                        action_label(turn, locale),
+                    )
+                }
+                OptionItem::GameSpeed => (
+                    locale.get("options.game_speed").to_owned(),
+                    format!("{}%", config.game_speed),
+                ),
+                OptionItem::ScreenShake => (
+                    locale.get("options.screen_shake").to_owned(),
+                    switch(config.screen_shake),
+                ),
+                OptionItem::HitStop => (
+                    locale.get("options.hit_stop").to_owned(),
+                    switch(config.hit_stop),
+                ),
+                OptionItem::PointerLabels => (
+                    locale.get("options.pointer_labels").to_owned(),
+                    switch(config.pointer_labels),
+                ),
+                OptionItem::ProceduralBackground => (
+                    locale.get("options.procedural_background").to_owned(),
+                    switch(config.procedural_background),
+                ),
+                OptionItem::Language => (
+                    locale.get("options.language").to_owned(),
+                    self.locale_name(&config.locale).to_owned(),
+                ),
+                OptionItem::Binding(action) => {
+                    let value = if self.rebinding && i == self.selected {
+                        "...".to_owned()
+                    } else {
+                        key_label(config.bindings.key(action))
+                    };
+                    (action_label(action, locale), value)
+                }
+                OptionItem::Back => (locale.get("options.back").to_owned(), String::new()),
+            })
+            .collect()
+    }
+
+    fn next_locale(&self, code: &str) -> String {
+        match self.locales.iter().position(|(c, _)| c == code) {
+            Some(idx) => self.locales[(idx + 1) % self.locales.len()].0.clone(),
+            None => self.locales
+                .first()
+                .map_or(code.to_owned(), |(c, _)| c.clone()),
+        }
+    }
+
+    fn locale_name<'a>(&'a self, code: &'a str) -> &'a str {
+        self.locales
+            .iter()
+            .find(|(c, _)| c == code)
+            .map_or(code, |(_, name)| name.as_str())
+    }
+
+    fn rebind(&mut self, key: Key, config: &mut Config) -> () {
+        self.rebinding = false;
+        // The quit key cancels instead of being bound
+        if key == config.bindings.key(GameAction::Quit) {
+            return;
+        }
+        if let OptionItem::Binding(action) = self.items[self.selected] {
+            if let Err(err) = config.bindings.bind(action, key) {
+                self.message = Some(err);
+            }
+        }
+    }
+}
+
+fn action_label(action: GameAction, locale: &Locale) -> String {
+    locale.get(&format!("action.{}", action.config_name())).to_owned()
+}
+
+// Left steps down, anything else steps up and wraps back round to the slowest speed
+fn step_game_speed(speed: u32, key: Key) -> u32 {
+    use settings::game_speed::{MAX, MIN, STEP};
+
+    match key {
+        Key::Left => speed.saturating_sub(STEP).max(MIN),
+        Key::Right => (speed + STEP).min(MAX),
+        _ if speed >= MAX => MIN,
+        _ => speed + STEP,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use controls::Bindings;
+    use settings;
+    use settings::game_speed::{MAX, MIN, STEP};
+    use std::path::PathBuf;
+
+    #[test]
+    fn it_should_clamp_game_speed() {
+        assert_eq!(step_game_speed(MIN, Key::Left), MIN);
+        assert_eq!(step_game_speed(MAX, Key::Right), MAX);
+        assert_eq!(step_game_speed(MAX, Key::Left), MAX - STEP);
+    }
+
+    #[test]
+    fn it_should_cycle_locales() {
+        let options = Options::new(vec![
+            ("en".to_owned(), "English".to_owned()),
+            ("de".to_owned(), "Deutsch".to_owned()),
+        ]);
+        assert_eq!(options.next_locale("en"), "de");
+        assert_eq!(options.next_locale("de"), "en");
+        assert_eq!(options.next_locale("xx"), "en");
+        assert_eq!(options.locale_name("de"), "Deutsch");
+        assert_eq!(options.locale_name("xx"), "xx");
+    }
+
+    #[test]
+    fn it_should_wrap_game_speed_on_return() {
+        assert_eq!(step_game_speed(MAX, Key::Return), MIN);
+        assert_eq!(step_game_speed(MIN, Key::Return), MIN + STEP);
+    }
+
+    #[test]
+    fn it_should_cycle_every_input_mode() {
+        use input::INPUT_MODES;
+
+        let mut options = Options::new(vec![]);
+        let mut config = Config::load(PathBuf::new()).0;
+        for &mode in INPUT_MODES.iter().cycle().skip(1).take(INPUT_MODES.len()) {
+            options.key_pressed(Key::Return, &mut config);
+            assert_eq!(config.input_mode, mode);
+        }
+    }
+
+    #[test]
+    fn it_should_cancel_rebinding_with_quit_key() {
+        let mut options = Options::new(vec![]);
+        let mut config = Config::load(PathBuf::new()).0;
+        let quit = config.bindings.key(GameAction::Quit);
+        options.selected = options
+            .items
+            .iter()
+            .position(|&item| item == OptionItem::Binding(GameAction::Pause))
+            .unwrap();
+
+        options.key_pressed(Key::Return, &mut config);
+        assert!(options.is_rebinding());
+        assert!(options.captures(quit, &config));
+
+        options.key_pressed(quit, &mut config);
+        assert!(!options.is_rebinding());
+        assert!(!options.captures(quit, &config));
+        assert_eq!(config.bindings, Bindings::new());
+        assert_eq!(options.message, None);
+    }
+
+    #[test]
+    fn it_should_scan_past_bindings_to_back() {
+        let mut options = Options::new(vec![]);
+        let mut config = Config::load(PathBuf::new()).0;
+
+        options.scan_to(1);
+        options.key_pressed(Key::Return, &mut config);
+        assert_eq!(config.hold_turns_right, !settings::input::HOLD_TURNS_RIGHT);
+        assert!(!options.take_closed());
+
+        options.scan_to(options.scan_count() - 1);
+        options.key_pressed(Key::Return, &mut config);
+        assert!(!options.is_rebinding());
+        assert!(options.take_closed());
+        assert!(!options.take_closed());
+    }
+}
@@ -0,0 +1,207 @@
+extern crate piston_window;
+
+use game::*;
+use piston_window::*;
+use tween::*;
+
+// Describes how an emitter's particles look and move. Size and colour change over each
+// particle's life, with keyframe fractions running from birth (0) to death (1).
+pub struct EmitterConfig {
+    pub rate: f64, // Particles per second while emitting continuously
+    pub lifetime: (f64, f64),
+    pub speed: (f64, f64),
+    pub spread: f64, // Degrees either side of the emit direction
+    pub size: &'static [KeyFrame],
+    pub color: [&'static [KeyFrame]; 4], // (red, green, blue, alpha)
+}
+
+struct Particle {
+    pos: Point,
+    velocity: Point,
+    age: f64,
+    lifetime: f64,
+    config: &'static EmitterConfig,
+}
+
+impl Particle {
+    fn life_frac(&self) -> f64 {
+        self.age / self.lifetime
+    }
+
+    fn size(&self) -> f64 {
+        sample_keyframes(self.config.size, self.life_frac())
+    }
+
+    fn color(&self) -> [f32; 4] {
+        let frac = self.life_frac();
+        let color = &self.config.color;
+        [
+            sample_keyframes(color[0], frac) as f32,
+            sample_keyframes(color[1], frac) as f32,
+            sample_keyframes(color[2], frac) as f32,
+            sample_keyframes(color[3], frac) as f32,
+        ]
+    }
+}
+
+// Every particle in the game, in world coordinates. The pool never grows past its budget, new
+// particles are dropped once it is full.
+pub struct Particles {
+    particles: Vec<Particle>,
+    budget: usize,
+}
+
+impl Particles {
+    pub fn new(budget: usize) -> Particles {
+        Particles {
+            particles: Vec::with_capacity(budget),
+            budget,
+        }
+    }
+
+    // Direction is in radians, and the base velocity is added to every particle
+    pub fn emit(
+        &mut self,
+        config: &'static EmitterConfig,
+        pos: Point,
+        direction: f64,
+        base_velocity: Point,
+        count: u32,
+    ) -> () {
+        for _ in 0..count {
+            if self.particles.len() >= self.budget {
+                return;
+            }
+
+            let spread = config.spread.to_radians();
+            let angle = direction + random_between((-spread, spread));
+            let speed = random_between(config.speed);
+            self.particles.push(Particle {
+                pos,
+                velocity: Point::new(angle.cos(), angle.sin()) * speed + base_velocity,
+                age: 0.0,
+                lifetime: random_between(config.lifetime),
+                config,
+            });
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) -> () {
+        for particle in self.particles.iter_mut() {
+            particle.age += dt;
+            particle.pos = particle.pos + particle.velocity * dt;
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+    }
+
+    pub fn draw(&self, c: piston_window::Context, g: &mut G2d) -> () {
+        for particle in self.particles.iter() {
+            let size = particle.size();
+            let rect = [
+                particle.pos.x - size / 2.0,
+                particle.pos.y - size / 2.0,
+                size,
+                size,
+            ];
+            ellipse(particle.color(), rect, c.transform, g);
+        }
+    }
+
+    pub fn clear(&mut self) -> () {
+        self.particles.clear();
+    }
+
+    pub fn count(&self) -> usize {
+        self.particles.len()
+    }
+}
+
+fn random_between((min, max): (f64, f64)) -> f64 {
+    use rand::{thread_rng, Rng};
+
+    if max > min {
+        thread_rng().gen_range(min, max)
+    } else {
+        min
+    }
+}
+
+// Emits particles steadily, carrying over fractions of a particle between updates
+pub struct Emitter {
+    config: &'static EmitterConfig,
+    pending: f64,
+}
+
+impl Emitter {
+    pub fn new(config: &'static EmitterConfig) -> Emitter {
+        Emitter {
+            config,
+            pending: 0.0,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        particles: &mut Particles,
+        pos: Point,
+        direction: f64,
+        base_velocity: Point,
+        dt: f64,
+    ) -> () {
+        self.pending += self.config.rate * dt;
+        let count = self.pending.floor();
+        self.pending -= count;
+        particles.emit(self.config, pos, direction, base_velocity, count as u32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_CONFIG: EmitterConfig = EmitterConfig {
+        rate: 10.0,
+        lifetime: (1.0, 1.0),
+        speed: (100.0, 100.0),
+        spread: 0.0,
+        size: &[(0.0, 4.0), (1.0, 0.0)],
+        color: [
+            &[(0.0, 1.0), (1.0, 1.0)],
+            &[(0.0, 1.0), (1.0, 0.0)],
+            &[(0.0, 0.0), (1.0, 0.0)],
+            &[(0.0, 1.0), (1.0, 0.0)],
+        ],
+    };
+
+    #[test]
+    fn it_should_emit_at_rate() {
+        let mut particles = Particles::new(100);
+        let mut emitter = Emitter::new(&TEST_CONFIG);
+        for _ in 0..4 {
+            emitter.update(&mut particles, Point::new(0.0, 0.0), 0.0, Point::new(0.0, 0.0), 0.25);
+        }
+        assert_eq!(particles.count(), 10);
+    }
+
+    #[test]
+    fn it_should_stay_within_budget() {
+        let mut particles = Particles::new(5);
+        particles.emit(&TEST_CONFIG, Point::new(0.0, 0.0), 0.0, Point::new(0.0, 0.0), 20);
+        assert_eq!(particles.count(), 5);
+    }
+
+    #[test]
+    fn it_should_age_and_remove_particles() {
+        let mut particles = Particles::new(5);
+        particles.emit(&TEST_CONFIG, Point::new(0.0, 0.0), 0.0, Point::new(0.0, 0.0), 1);
+
+        particles.update(0.5);
+        assert_eq!(particles.count(), 1);
+        assert_approx_eq!(particles.particles[0].pos.x, 50.0);
+        assert_approx_eq!(particles.particles[0].size(), 2.0);
+        assert_approx_eq!(particles.particles[0].color()[1] as f64, 0.5);
+
+        particles.update(0.6);
+        assert_eq!(particles.count(), 0);
+    }
+}
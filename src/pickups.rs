@@ -1,9 +1,11 @@
 extern crate piston_window;
 
+use animation::{Animation, Clips};
+use camera::Camera;
 use game::*;
 use piston_window::*;
 use player::*;
-use sprite::*;
+use sprite::Sprite;
 use traits::Collides;
 use tween::*;
 
@@ -18,6 +20,7 @@ enum State {
 pub struct Pickup {
     state: State,
     pub collider: Collider,
+    sprite: Animation,
     time_alive: f64,
     rot_tween: Tween,
     collect_opacity_tween: Tween,
@@ -38,13 +41,14 @@ impl Collides for Pickup {
 }
 
 impl Pickup {
-    pub fn new(mut collider: Collider) -> Pickup {
+    pub fn new(mut collider: Collider, sprite: Animation) -> Pickup {
         use settings::pickup::*;
 
         collider.disable();
         Pickup {
             state: State::Inactive,
             collider,
+            sprite,
             time_alive: 0.0,
             rot_tween: Tween::new(
                 vec![(0.0, 0.0), (1.0, 360.0)],
@@ -85,7 +89,7 @@ impl Pickup {
         }
     }
 
-    pub fn update(&mut self, player: &Player, dt: f64) {
+    pub fn update(&mut self, dt: f64) {
         use settings::pickup;
 
         match self.state {
@@ -93,9 +97,6 @@ impl Pickup {
                 self.time_alive += dt;
                 self.rot_tween.update(dt);
 
-                // Update position based off player movement
-                self.collider.pos = self.collider.pos - player.velocity() * dt;
-
                 if self.time_alive > pickup::MAX_TIME {
                     self.disappear();
                 }
@@ -105,9 +106,6 @@ impl Pickup {
                 self.collect_rot_tween.update(dt);
                 self.grow_tween.update(dt);
 
-                // Update position based off player movement
-                self.collider.pos = self.collider.pos - player.velocity() * dt;
-
                 if !self.collect_opacity_tween.is_playing() {
                     self.reset();
                 }
@@ -117,61 +115,56 @@ impl Pickup {
                 self.rot_tween.update(dt);
                 self.shrink_tween.update(dt);
 
-                // Update position based off player movement
-                self.collider.pos = self.collider.pos - player.velocity() * dt;
-
                 if !self.disappear_opacity_tween.is_playing() {
                     self.reset();
                 }
             }
             State::Inactive => {}
         }
+        self.sprite.update(dt);
     }
 
     pub fn draw(
         &mut self,
-        sprite: &mut Sprite<G2dTexture>,
+        clips: &Clips,
         pointer: &mut Sprite<G2dTexture>,
+        camera: &Camera,
         c: piston_window::Context,
         g: &mut G2d,
     ) -> () {
-        use offscreen::draw_offscreen;
+        use offscreen::{draw_anim_offscreen, place_pointer};
         use settings::pickup;
         use settings::pickup::POINTER_COLOR;
 
-        match self.state {
-            State::Active => {
-                sprite.set_position(self.collider.pos.x, self.collider.pos.y);
-                sprite.set_rotation(self.rot_tween.get_val());
-                sprite.draw(c.transform, g);
-
-                draw_offscreen(sprite, pointer, self.collider.pos, POINTER_COLOR, c, g);
-            }
-            State::Collected => {
-                sprite.set_position(self.collider.pos.x, self.collider.pos.y);
-                sprite.set_rotation(self.collect_rot_tween.get_val());
-                sprite.set_scale(self.grow_tween.get_val(), self.grow_tween.get_val());
-                sprite.set_opacity(self.collect_opacity_tween.get_val() as f32);
-                sprite.draw(c.transform, g);
-
-                // Reset scale and opacity
-                sprite.set_scale(pickup::SCALE, pickup::SCALE);
-                sprite.set_opacity(1.0);
-            }
-            State::Disappearing => {
-                sprite.set_position(self.collider.pos.x, self.collider.pos.y);
-                sprite.set_rotation(self.rot_tween.get_val());
-                sprite.set_scale(self.shrink_tween.get_val(), self.shrink_tween.get_val());
-                sprite.set_opacity(self.disappear_opacity_tween.get_val() as f32);
-                sprite.draw(c.transform, g);
-
-                draw_offscreen(sprite, pointer, self.collider.pos, POINTER_COLOR, c, g);
+        let world = camera.transform(c);
+        let placed = place_pointer(camera.to_screen(self.collider.pos), camera.viewport());
 
-                // Reset scale and opacity
-                sprite.set_scale(pickup::SCALE, pickup::SCALE);
-                sprite.set_opacity(1.0);
+        let (rotation, scale, opacity) = match self.state {
+            State::Active => (self.rot_tween.get_val(), pickup::SCALE, 1.0),
+            State::Collected => (
+                self.collect_rot_tween.get_val(),
+                self.grow_tween.get_val(),
+                self.collect_opacity_tween.get_val(),
+            ),
+            State::Disappearing => (
+                self.rot_tween.get_val(),
+                self.shrink_tween.get_val(),
+                self.disappear_opacity_tween.get_val(),
+            ),
+            State::Inactive => return,
+        };
+
+        self.sprite.set_pos(self.collider.pos);
+        self.sprite.set_rotation(rotation);
+        self.sprite.set_scale(scale);
+        self.sprite.set_opacity(opacity as f32);
+        self.sprite.draw(clips, world, g);
+
+        // No pointer once collected, the player is right next to it
+        if self.state != State::Collected {
+            if let Some(placed) = placed {
+                draw_anim_offscreen(&self.sprite, clips, pointer, placed, POINTER_COLOR, c, g);
             }
-            State::Inactive => {}
         }
     }
 
@@ -194,16 +187,33 @@ impl Pickup {
     }
 
     pub fn place(&mut self, pos: Point) -> () {
-        self.collider.pos = pos;
+        self.collider.place(pos);
         self.state = State::Active;
         self.collider.enable();
         self.rot_tween.reset();
+        self.sprite.play();
         self.time_alive = 0.0;
     }
 
+    pub fn is_active(&self) -> bool {
+        self.state == State::Active
+    }
+
+    // Seconds before the pickup disappears by itself
+    pub fn time_left(&self) -> Option<f64> {
+        use settings::pickup::MAX_TIME;
+
+        if self.is_active() {
+            Some((MAX_TIME - self.time_alive).max(0.0))
+        } else {
+            None
+        }
+    }
+
     pub fn reset(&mut self) -> () {
         self.state = State::Inactive;
         self.collider.disable();
+        self.sprite.stop();
 
         self.rot_tween.stop();
 
@@ -213,13 +223,17 @@ impl Pickup {
     }
 }
 
-pub fn initialise_pickups() -> Vec<Pickup> {
+pub fn initialise_pickups(clips: &Clips) -> Vec<Pickup> {
     use settings::{game, pickup};
 
     let mut pickups: Vec<Pickup> = vec![];
+    let clip = clips.get(pickup::CLIP);
 
     for _ in 0..game::MAX_PICKUPS {
-        let pickup = Pickup::new(Collider::new(Point::new(0.0, 0.0), pickup::COLLIDER_RADIUS));
+        let pickup = Pickup::new(
+            Collider::new(Point::new(0.0, 0.0), pickup::COLLIDER_RADIUS),
+            Animation::new(clip.clone(), pickup::SCALE),
+        );
 
         pickups.push(pickup);
     }
@@ -227,9 +241,9 @@ pub fn initialise_pickups() -> Vec<Pickup> {
     pickups
 }
 
-fn place_pickup(pickup: &mut Pickup) -> () {
+fn place_pickup(pickup: &mut Pickup, centre: Point) -> () {
     use rand::{thread_rng, Rng};
-    use settings::{pickup_generator, window};
+    use settings::pickup_generator;
 
     let mut rng = thread_rng();
     let angle = rng.gen_range(0.0, ::std::f64::consts::PI * 2.0);
@@ -238,10 +252,9 @@ fn place_pickup(pickup: &mut Pickup) -> () {
         pickup_generator::MAX_SPAWN_RADIUS,
     );
 
-    let (width, height) = window::SIZE;
     let pos = Point::new(
-        (width as f64) / 2.0 - (angle.cos() * radius),
-        (height as f64) / 2.0 - (angle.sin() * radius),
+        centre.x - (angle.cos() * radius),
+        centre.y - (angle.sin() * radius),
     );
 
     pickup.place(pos);
@@ -278,7 +291,7 @@ impl Generator {
 
         if place_new_pickup {
             if let Some(idx) = pickups.iter().position(|m| m.state == State::Inactive) {
-                place_pickup(&mut pickups[idx]);
+                place_pickup(&mut pickups[idx], player.collider.pos);
             }
         }
     }
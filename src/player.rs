@@ -1,8 +1,12 @@
 extern crate piston_window;
 
+use animation::{Animation, AnimationEvent, Clip, Clips};
 use game::*;
+use input::Steering;
+use particles::{Emitter, Particles};
 use piston_window::*;
-use sprite::Sprite;
+use ship::Ship;
+use std::rc::Rc;
 use traits::Collides;
 
 #[derive(Copy, Clone)]
@@ -22,8 +26,16 @@ pub enum State {
 pub struct Player {
     pub state: State,
     pub collider: Collider,
+    sprite: Animation,
+    banking: [Rc<Clip>; 3], // Banking left, level and banking right
     explosion: Animation,
     rot: f64,
+    target: Option<f64>,
+    ship: Ship,
+    boost_time: f64,
+    boost_cooldown: f64,
+    exhaust: Emitter,
+    burst_pending: bool, // Sparks still to be thrown out for the explosion
 }
 
 impl Collides for Player {
@@ -37,26 +49,59 @@ impl Collides for Player {
 }
 
 impl Player {
-    pub fn new(collider: Collider, explosion: Animation) -> Player {
+    pub fn new(
+        ship: Ship,
+        banking: [Rc<Clip>; 3],
+        collider: Collider,
+        explosion: Animation,
+    ) -> Player {
+        use settings::particles::PLAYER_EXHAUST;
+
+        let mut sprite = Animation::new(banking[1].clone(), ship.scale);
+        sprite.play();
         Player {
             state: State::Active(Action::NoMove),
             collider,
+            sprite,
+            banking,
             explosion,
             rot: 0.0,
+            target: None,
+            ship,
+            boost_time: 0.0,
+            boost_cooldown: 0.0,
+            exhaust: Emitter::new(&PLAYER_EXHAUST),
+            burst_pending: false,
         }
     }
 
     pub fn update(&mut self, dt: f64) {
-        use settings::player;
+        self.boost_time = (self.boost_time - dt).max(0.0);
+        self.boost_cooldown = (self.boost_cooldown - dt).max(0.0);
+
         match self.state {
-            State::Active(action) => match action {
-                Action::Left => self.rot = self.rot - player::ANGULAR_VELOCITY * dt,
-                Action::Right => self.rot = self.rot + player::ANGULAR_VELOCITY * dt,
-                Action::NoMove => (),
-            },
+            State::Active(action) => {
+                match self.target {
+                    Some(target) => self.turn_towards(target, dt),
+                    None => match action {
+                        Action::Left => self.rot -= self.ship.angular_velocity * dt,
+                        Action::Right => self.rot += self.ship.angular_velocity * dt,
+                        Action::NoMove => (),
+                    },
+                }
+                let pos = self.collider.pos + self.velocity() * dt;
+                self.collider.move_to(pos);
+                self.collider.set_rotation(self.rot);
+
+                // Ship animations run faster while boosting
+                let speed = self.velocity().magnitude() / self.ship.speed;
+                let clip = self.banking_clip().clone();
+                self.sprite.set_clip(&clip);
+                self.sprite.set_speed(speed);
+                self.sprite.update(dt);
+            }
             State::Exploding => {
-                self.explosion.update(dt);
-                if !self.explosion.is_playing() {
+                if let Some(AnimationEvent::Finished) = self.explosion.update(dt) {
                     self.state = State::Inactive;
                 }
             }
@@ -64,7 +109,20 @@ impl Player {
         }
     }
 
+    pub fn steer(&mut self, steering: Steering) -> () {
+        match steering {
+            Steering::Digital(left_key, right_key) => self.input(left_key, right_key),
+            Steering::Heading(target) => {
+                if self.is_active() {
+                    self.target = Some(target);
+                }
+            }
+            Steering::Neutral => self.input(KeyState::NotPressed, KeyState::NotPressed),
+        }
+    }
+
     pub fn input(&mut self, left_key: KeyState, right_key: KeyState) -> () {
+        self.target = None;
         match self.state {
             State::Active(_) => match (left_key, right_key) {
                 (KeyState::Pressed, KeyState::Pressed) => {
@@ -84,22 +142,15 @@ impl Player {
         }
     }
 
-    pub fn draw(
-        &mut self,
-        sprites: &mut [Sprite<G2dTexture>; 3],
-        explosion_tex: &mut AnimTexture,
-        c: piston_window::Context,
-        g: &mut G2d,
-    ) -> () {
+    pub fn draw(&mut self, clips: &Clips, c: piston_window::Context, g: &mut G2d) -> () {
         match self.state {
-            State::Active(action) => {
-                let mut sprite = self.active_sprite(sprites, action);
-                sprite.set_rotation(self.rot);
-                sprite.set_position(self.collider.pos.x, self.collider.pos.y);
-                sprite.draw(c.transform, g);
+            State::Active(_) => {
+                self.sprite.set_rotation(self.rot);
+                self.sprite.set_pos(self.collider.pos);
+                self.sprite.draw(clips, c, g);
             }
             State::Exploding => {
-                self.explosion.draw(explosion_tex, c, g);
+                self.explosion.draw(clips, c, g);
             }
             State::Inactive => {}
         }
@@ -110,6 +161,7 @@ impl Player {
             State::Active(_) => {
                 self.state = State::Exploding;
                 self.collider.disable();
+                self.burst_pending = true;
                 self.explosion.play();
                 self.explosion.set_pos(self.collider.pos);
             }
@@ -120,13 +172,69 @@ impl Player {
     pub fn reset(&mut self) -> () {
         self.state = State::Active(Action::NoMove);
         self.rot = 0.0;
+        self.collider.set_rotation(self.rot);
+        self.target = None;
+        self.boost_time = 0.0;
+        self.boost_cooldown = 0.0;
+        self.burst_pending = false;
         self.collider.enable();
         self.explosion.stop();
     }
 
+    // Exhaust while flying, and a burst of sparks and debris when the ship explodes
+    pub fn emit_particles(&mut self, particles: &mut Particles, dt: f64) -> () {
+        use settings::particles::*;
+
+        let no_velocity = Point::new(0.0, 0.0);
+        match self.state {
+            State::Active(_) => {
+                let heading = self.velocity().normalized();
+                let pos = self.collider.pos - heading * PLAYER_EXHAUST_OFFSET;
+                let direction = (self.rot + 180.0).to_radians();
+                self.exhaust.update(particles, pos, direction, no_velocity, dt);
+            }
+            State::Exploding => {
+                if self.burst_pending {
+                    let pos = self.collider.pos;
+                    particles.emit(&SPARKS, pos, 0.0, no_velocity, PLAYER_SPARKS);
+                    particles.emit(&DEBRIS, pos, 0.0, no_velocity, PLAYER_DEBRIS);
+                    self.burst_pending = false;
+                }
+            }
+            State::Inactive => {}
+        }
+    }
+
+    pub fn boost(&mut self) -> () {
+        use settings::player::{BOOST_COOLDOWN, BOOST_LENGTH};
+
+        if self.is_active() && self.boost_cooldown <= 0.0 {
+            self.boost_time = BOOST_LENGTH;
+            self.boost_cooldown = BOOST_COOLDOWN;
+        }
+    }
+
+    pub fn set_ship(&mut self, ship: Ship, banking: [Rc<Clip>; 3]) -> () {
+        self.ship = ship;
+        self.collider.set_shape(ship.hitbox);
+        self.sprite = Animation::new(banking[1].clone(), ship.scale);
+        self.sprite.play();
+        self.banking = banking;
+    }
+
+    pub fn ship(&self) -> Ship {
+        self.ship
+    }
+
     pub fn velocity(&self) -> Point {
-        use settings::player;
-        Point::new(self.rot.to_radians().cos(), self.rot.to_radians().sin()) * player::SPEED
+        use settings::player::BOOST_MULTIPLIER;
+
+        let speed = if self.boost_time > 0.0 {
+            self.ship.speed * BOOST_MULTIPLIER
+        } else {
+            self.ship.speed
+        };
+        Point::new(self.rot.to_radians().cos(), self.rot.to_radians().sin()) * speed
     }
 
     pub fn is_active(&self) -> bool {
@@ -136,28 +244,51 @@ impl Player {
         }
     }
 
-    fn active_sprite<'a>(
-        &mut self,
-        sprites: &'a mut [Sprite<G2dTexture>; 3],
-        action: Action,
-    ) -> &'a mut Sprite<G2dTexture> {
-        match action {
-            Action::Left => &mut sprites[0],
-            Action::NoMove => &mut sprites[1],
-            Action::Right => &mut sprites[2],
+    // Turn the shortest way round towards the target heading, no faster than the ship allows
+    fn turn_towards(&mut self, target: f64, dt: f64) -> () {
+        let max_turn = self.ship.angular_velocity * dt;
+        let diff = ((target - self.rot) % 360.0 + 540.0) % 360.0 - 180.0;
+
+        if diff.abs() <= max_turn {
+            self.rot += diff;
+            self.state = State::Active(Action::NoMove);
+        } else if diff < 0.0 {
+            self.rot -= max_turn;
+            self.state = State::Active(Action::Left);
+        } else {
+            self.rot += max_turn;
+            self.state = State::Active(Action::Right);
+        }
+    }
+
+    fn banking_clip(&self) -> &Rc<Clip> {
+        match self.state {
+            State::Active(Action::Left) => &self.banking[0],
+            State::Active(Action::Right) => &self.banking[2],
+            _ => &self.banking[1],
         }
     }
 }
 
-pub fn initialise_player_sprites(
-    window: &mut PistonWindow,
-    folder: &::std::path::PathBuf,
-    files: [&str; 3],
-    scale: f64,
-) -> [Sprite<G2dTexture>; 3] {
-    let spr_player_left = load_sprite(window, folder, files[0], scale);
-    let spr_player_mid = load_sprite(window, folder, files[1], scale);
-    let spr_player_right = load_sprite(window, folder, files[2], scale);
-
-    [spr_player_left, spr_player_mid, spr_player_right]
+// The clips a ship banks between as it turns
+pub fn ship_clips(ship: Ship, clips: &Clips) -> [Rc<Clip>; 3] {
+    [
+        clips.get(ship.clips[0]),
+        clips.get(ship.clips[1]),
+        clips.get(ship.clips[2]),
+    ]
+}
+
+// A ship with blank clips and a unit hitbox at the origin, for tests that don't draw anything
+#[cfg(test)]
+pub fn test_player() -> Player {
+    use animation::{test_clip, PlayMode};
+
+    let ship_clip = test_clip(&[1.0], PlayMode::Loop);
+    Player::new(
+        ::ship::ROSTER[0],
+        [ship_clip.clone(), ship_clip.clone(), ship_clip],
+        Collider::new(Point::new(0.0, 0.0), 1.0),
+        Animation::new(test_clip(&[1.0], PlayMode::Once), 1.0),
+    )
 }
@@ -0,0 +1,104 @@
+extern crate piston_window;
+
+use game::*;
+use layout::place;
+use missile::Missile;
+use pickups::Pickup;
+use piston_window::*;
+use player::Player;
+use settings::radar::*;
+use viewport::Viewport;
+
+// Plots every missile and pickup around the player on a circular display in the corner
+pub fn draw_radar(
+    player: &Player,
+    missiles: &[Missile],
+    pickups: &[Pickup],
+    viewport: &Viewport,
+    c: piston_window::Context,
+    g: &mut G2d,
+) -> () {
+    let size = Point::new(RADIUS * 2.0, RADIUS * 2.0);
+    let area = Point::new(viewport.width(), viewport.height());
+    let centre = place(ANCHOR, MARGIN, size, area) + size / 2.0;
+    let rect = [centre.x - RADIUS, centre.y - RADIUS, RADIUS * 2.0, RADIUS * 2.0];
+
+    ellipse(BACKGROUND_COLOR, rect, c.transform, g);
+    Ellipse::new_border(BORDER_COLOR, BORDER_RADIUS).draw(rect, &c.draw_state, c.transform, g);
+    draw_blip(centre, PLAYER_COLOR, c, g);
+
+    let origin = player.collider.pos;
+
+    for pickup in pickups.iter().filter(|p| p.is_active()) {
+        use settings::pickup::POINTER_COLOR;
+
+        if let Some((pos, opacity)) = place_blip(pickup.collider.pos - origin) {
+            draw_blip(centre + pos, with_opacity(POINTER_COLOR, opacity), c, g);
+        }
+    }
+
+    for missile in missiles.iter().filter(|m| m.is_active()) {
+        use settings::missile::POINTER_COLOR;
+
+        let color = if player.is_active() && missile.is_homing_on(origin) {
+            HOMING_COLOR
+        } else {
+            POINTER_COLOR
+        };
+        if let Some((pos, opacity)) = place_blip(missile.collider.pos - origin) {
+            draw_blip(centre + pos, with_opacity(color, opacity), c, g);
+        }
+    }
+}
+
+fn draw_blip(pos: Point, color: [f32; 4], c: piston_window::Context, g: &mut G2d) -> () {
+    let rect = [
+        pos.x - BLIP_RADIUS,
+        pos.y - BLIP_RADIUS,
+        BLIP_RADIUS * 2.0,
+        BLIP_RADIUS * 2.0,
+    ];
+    ellipse(color, rect, c.transform, g);
+}
+
+fn with_opacity(color: [f32; 3], opacity: f32) -> [f32; 4] {
+    [color[0], color[1], color[2], opacity]
+}
+
+// Position of the blip relative to the radar's centre and how opaque it is, for an object at
+// the given offset from the player. Anything out of range isn't shown.
+fn place_blip(offset: Point) -> Option<(Point, f32)> {
+    let distance = offset.magnitude();
+    if distance > RANGE {
+        return None;
+    }
+
+    let fraction = distance / RANGE;
+    let opacity = 1.0 - (1.0 - MIN_BLIP_OPACITY) * fraction as f32;
+    Some((offset * ((RADIUS - BLIP_RADIUS) / RANGE), opacity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_hide_objects_out_of_range() {
+        assert!(place_blip(Point::new(RANGE + 1.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn it_should_keep_blips_inside_radar() {
+        let (pos, _) = place_blip(Point::new(0.0, -RANGE)).unwrap();
+        assert_approx_eq!(pos.x, 0.0);
+        assert_approx_eq!(pos.y, -(RADIUS - BLIP_RADIUS));
+    }
+
+    #[test]
+    fn it_should_fade_blips_with_distance() {
+        let (_, near) = place_blip(Point::new(0.0, 0.0)).unwrap();
+        let (_, far) = place_blip(Point::new(RANGE, 0.0)).unwrap();
+        assert_approx_eq!(near, 1.0);
+        assert_approx_eq!(far, MIN_BLIP_OPACITY);
+    }
+}
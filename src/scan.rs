@@ -0,0 +1,73 @@
+// Steps through a menu's choices on a timer, so one-button players can pick one by pressing
+// their button when it comes round
+pub struct Scanner {
+    choice: usize,
+    timer: f64,
+}
+
+impl Scanner {
+    pub fn new() -> Scanner {
+        Scanner {
+            choice: 0,
+            timer: 0.0,
+        }
+    }
+
+    // Back to the first choice, with the full interval to press for it
+    pub fn reset(&mut self) -> () {
+        self.choice = 0;
+        self.timer = 0.0;
+    }
+
+    // Moves on to the next of `count` choices each interval, returning whether it moved
+    pub fn update(&mut self, dt: f64, count: usize) -> bool {
+        use settings::input::SCAN_INTERVAL;
+
+        if count == 0 {
+            return false;
+        }
+
+        self.timer += dt;
+        let mut moved = false;
+        while self.timer >= SCAN_INTERVAL {
+            self.timer -= SCAN_INTERVAL;
+            self.choice = (self.choice + 1) % count;
+            moved = true;
+        }
+        moved
+    }
+
+    pub fn choice(&self) -> usize {
+        self.choice
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use settings::input::SCAN_INTERVAL;
+
+    #[test]
+    fn it_should_step_through_choices_and_wrap() {
+        let mut scanner = Scanner::new();
+
+        assert!(!scanner.update(SCAN_INTERVAL * 0.5, 3));
+        assert_eq!(scanner.choice(), 0);
+        assert!(scanner.update(SCAN_INTERVAL * 0.5, 3));
+        assert_eq!(scanner.choice(), 1);
+        assert!(scanner.update(SCAN_INTERVAL * 2.0, 3));
+        assert_eq!(scanner.choice(), 0);
+
+        scanner.update(SCAN_INTERVAL, 3);
+        scanner.reset();
+        assert_eq!(scanner.choice(), 0);
+        assert!(!scanner.update(SCAN_INTERVAL * 0.5, 3));
+    }
+
+    #[test]
+    fn it_should_stay_put_with_no_choices() {
+        let mut scanner = Scanner::new();
+        assert!(!scanner.update(SCAN_INTERVAL * 3.0, 0));
+        assert_eq!(scanner.choice(), 0);
+    }
+}
@@ -8,45 +8,253 @@ pub mod game {
     // Score
     pub const POINTS_PER_MISSILE: u32 = 100;
     pub const POINTS_PER_PICKUP: u32 = 500;
+    pub const POINTS_PER_NEAR_MISS: u32 = 250;
+}
+
+pub mod config {
+    pub const FILE: &str = "config.txt";
+}
+
+pub mod locale {
+    pub const DEFAULT: &str = "en";
+    pub const FOLDER: &str = "locales";
+}
+
+pub mod controls {
+    use piston_window::Key;
+
+    // Default bindings, used until changed from the options screen
+    pub const TURN_LEFT: Key = Key::Left;
+    pub const TURN_RIGHT: Key = Key::Right;
+    pub const RESTART: Key = Key::Space;
+    pub const CHANGE_SHIP: Key = Key::Backspace;
+    pub const PAUSE: Key = Key::P;
+    pub const BOOST: Key = Key::Up;
+    pub const DEBUG: Key = Key::F3;
+    pub const OPTIONS: Key = Key::Tab;
+    pub const QUIT: Key = Key::Escape;
+}
+
+pub mod gamepad {
+    // Standard controller button layout
+    pub const BUTTON_RESTART: u8 = 0; // A
+    pub const BUTTON_BOOST: u8 = 2; // X
+    pub const BUTTON_PAUSE: u8 = 6; // Start
+    pub const DPAD_LEFT: u8 = 13;
+    pub const DPAD_RIGHT: u8 = 14;
+
+    // How far the stick must be pushed sideways to count as turning
+    pub const STICK_TURN_THRESHOLD: f64 = 0.5;
+}
+
+pub mod input {
+    use input::InputMode;
+
+    // Default input mode, used until changed from the options screen
+    pub const DEFAULT_MODE: InputMode = InputMode::Keyboard;
+
+    // Default for which way the ship turns while the button is held in one-button hold mode
+    pub const HOLD_TURNS_RIGHT: bool = true;
+
+    // Seconds menus spend on each choice when stepping through them for one-button players
+    pub const SCAN_INTERVAL: f64 = 1.5;
+
+    // Cursor distance from the ship below which the mouse is ignored
+    pub const MOUSE_DEADZONE: f64 = 20.0;
+
+    pub const STICK_X_AXIS: u8 = 0;
+    pub const STICK_Y_AXIS: u8 = 1;
+    pub const STICK_DEADZONE: f64 = 0.3;
+}
+
+pub mod game_speed {
+    // Percentage of normal speed the simulation runs at
+    pub const DEFAULT: u32 = 100;
+    pub const MIN: u32 = 50;
+    pub const MAX: u32 = 100;
+    pub const STEP: u32 = 10;
 }
 
 pub mod window {
+    // Initial window size, the window can be resized afterwards
     pub const SIZE: (u32, u32) = (1440, 720);
+
+    // Everything is laid out on a logical screen of this height, scaled to fit the window
+    pub const LOGICAL_HEIGHT: f64 = 720.0;
+    pub const MIN_ASPECT: f64 = 4.0 / 3.0;
+    pub const MAX_ASPECT: f64 = 21.0 / 9.0;
+    pub const LETTERBOX_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
 }
 
-pub mod player {
-    pub const SPEED: f64 = 220.0;
-    pub const ANGULAR_VELOCITY: f64 = 130.0; // Degrees per second
-    pub const SCALE: f64 = 1.0;
+pub mod camera {
+    // How quickly the camera catches up with the player, higher is tighter
+    pub const SMOOTHING: f64 = 6.0;
+    // Seconds of player movement the camera looks ahead by
+    pub const LOOK_AHEAD: f64 = 0.25;
+    pub const ZOOM: f64 = 1.0;
+}
 
-    // Collider
-    pub const COLLIDER_RADIUS: f64 = 16.0;
+pub mod screen_shake {
+    // Both can be turned off from the options screen
+    pub const ENABLED: bool = true;
+    pub const HIT_STOP: bool = true;
+
+    // Trauma added by each explosion, out of a maximum of 1
+    pub const MISSILE_TRAUMA: f64 = 0.2;
+    pub const PLAYER_TRAUMA: f64 = 0.8;
+    pub const TRAUMA_DECAY: f64 = 1.2; // Per second
+    pub const MAX_OFFSET: f64 = 24.0;
+
+    // Updates the world freezes for when the player dies
+    pub const HIT_STOP_FRAMES: u32 = 6;
+}
+
+pub mod assets {
+    pub const MANIFEST: &str = "assets.txt";
+
+    // Checkerboard drawn in place of textures that couldn't be loaded
+    pub const PLACEHOLDER_SIZE: u32 = 64;
+    pub const PLACEHOLDER_CHECK: u32 = 8;
+    pub const PLACEHOLDER_COLORS: [[u8; 4]; 2] = [[255, 0, 255, 255], [0, 0, 0, 255]];
+}
+
+pub mod atlas {
+    pub const MAX_WIDTH: u32 = 1024;
+    pub const PADDING: u32 = 2; // Stops neighbouring sprites bleeding into each other
+}
+
+pub mod animation {
+    pub const MANIFEST: &str = "animations.txt";
+    pub const FRAME_TIME: f64 = 0.1; // Used when a clip doesn't give its own
+}
+
+pub mod player {
+    // Boost
+    pub const BOOST_MULTIPLIER: f64 = 1.6;
+    pub const BOOST_LENGTH: f64 = 0.8;
+    pub const BOOST_COOLDOWN: f64 = 4.0;
 
     // Explosion Settings
-    pub const EXPLOSION_LENGTH: f64 = 2.0;
+    pub const EXPLOSION_CLIP: &str = "player_explosion";
     pub const EXPLOSION_SCALE: f64 = 1.0;
 }
 
+pub mod ship {
+    pub mod balanced {
+        use game::Point;
+
+        pub const NAME: &str = "Balanced";
+        pub const CLIPS: [&str; 3] = ["balanced_left", "balanced", "balanced_right"];
+        pub const SPEED: f64 = 220.0;
+        pub const ANGULAR_VELOCITY: f64 = 130.0; // Degrees per second
+        pub const SCALE: f64 = 1.0;
+        // Hitbox round the hull and wings, nose first
+        pub const HITBOX: &[Point] = &[
+            Point { x: 15.0, y: 0.0 },
+            Point { x: -3.0, y: 17.0 },
+            Point { x: -14.0, y: 13.0 },
+            Point { x: -14.0, y: -13.0 },
+            Point { x: -3.0, y: -17.0 },
+        ];
+    }
+
+    pub mod interceptor {
+        use game::Point;
+
+        pub const NAME: &str = "Interceptor";
+        pub const CLIPS: [&str; 3] = ["interceptor_left", "interceptor", "interceptor_right"];
+        pub const SPEED: f64 = 290.0;
+        pub const ANGULAR_VELOCITY: f64 = 105.0; // Degrees per second
+        pub const SCALE: f64 = 0.9;
+        // Hitbox round the hull and wings, nose first
+        pub const HITBOX: &[Point] = &[
+            Point { x: 18.0, y: 0.0 },
+            Point { x: -4.0, y: 20.0 },
+            Point { x: -17.0, y: 15.0 },
+            Point { x: -17.0, y: -15.0 },
+            Point { x: -4.0, y: -20.0 },
+        ];
+    }
+
+    pub mod fighter {
+        use game::Point;
+
+        pub const NAME: &str = "Fighter";
+        pub const CLIPS: [&str; 3] = ["fighter_left", "fighter", "fighter_right"];
+        pub const SPEED: f64 = 180.0;
+        pub const ANGULAR_VELOCITY: f64 = 175.0; // Degrees per second
+        pub const SCALE: f64 = 0.8;
+        // Hitbox round the hull and wings, nose first
+        pub const HITBOX: &[Point] = &[
+            Point { x: 11.0, y: 0.0 },
+            Point { x: -2.0, y: 13.0 },
+            Point { x: -10.0, y: 10.0 },
+            Point { x: -10.0, y: -10.0 },
+            Point { x: -2.0, y: -13.0 },
+        ];
+    }
+}
+
 pub mod missile {
+    use game::Shape;
+
+    pub const FILE: &str = "missile.png";
     pub const MAX_SPEED: f64 = 390.0;
     pub const ACCELERATION: f64 = 445.0;
     pub const POINTER_COLOR: [f32; 3] = [0.74, 0.84, 0.86];
     pub const SCALE: f64 = 1.0;
 
-    // Collider
-    pub const COLLIDER_RADIUS: f64 = 10.0;
+    // Warning shown at the edge of the screen before a missile arrives
+    pub const WARNING_LENGTH: f64 = 1.0;
+    pub const WARNING_COLOR: [f32; 3] = [0.86, 0.2, 0.18];
+
+    // Missiles heading within this many degrees of the player count as homing in
+    pub const HOMING_ANGLE: f64 = 20.0;
+
+    // Collider, running nose to tail
+    pub const COLLIDER: Shape = Shape::Capsule {
+        half_length: 8.0,
+        r: 7.0,
+    };
 
     // Explosion Settings
-    pub const EXPLOSION_LENGTH: f64 = 1.0;
+    pub const EXPLOSION_CLIP: &str = "missile_explosion";
     pub const EXPLOSION_SCALE: f64 = 0.7;
 }
 
+pub mod near_miss {
+    // Gap between the hitboxes a missile has to get inside of to count
+    pub const MARGIN: f64 = 25.0;
+
+    // Ring that spreads out from the missile when a near miss is scored
+    pub const FLASH_LENGTH: f64 = 0.4;
+    pub const FLASH_COLOR: [f32; 4] = [1.0, 0.84, 0.2, 1.0];
+    pub const FLASH_THICKNESS: f64 = 2.0;
+}
+
+pub mod collision {
+    // Side of the spatial hash cells, a few missiles across
+    pub const CELL_SIZE: f64 = 64.0;
+    // Fewer objects than this are checked pair by pair. A rough guess rather than a measured
+    // crossover; the missile cap stays well under it, so only larger swarms use the grid.
+    pub const BROAD_PHASE_MIN: usize = 128;
+
+    // Shapes other than circles slide along their sweep in at most this many steps, stopping
+    // once they come within the tolerance of touching
+    pub const SWEEP_STEPS: usize = 32;
+    pub const SWEEP_TOLERANCE: f64 = 0.01;
+
+    // Points in each rounded end of a capsule when drawing hitboxes
+    pub const DEBUG_ARC_POINTS: usize = 8;
+}
+
 pub mod missile_generator {
     pub const SPAWN_RADIUS: f64 = 1200.0;
     pub const TIME_TO_APPEAR: f64 = 10.0;
 }
 
 pub mod pickup {
+    pub const CLIP: &str = "star";
     pub const ROTATION_PERIOD: f64 = 8.0;
     pub const MAX_TIME: f64 = 20.0;
     pub const SCALE: f64 = 1.0;
@@ -68,50 +276,302 @@ pub mod pickup_generator {
     pub const TIME_TO_APPEAR: f64 = 8.0;
 }
 
+pub mod particles {
+    use particles::EmitterConfig;
+
+    // Most particles alive at once, extra ones aren't spawned
+    pub const BUDGET: usize = 800;
+
+    // Distance behind the ship or missile that exhaust comes out
+    pub const PLAYER_EXHAUST_OFFSET: f64 = 28.0;
+    pub const MISSILE_EXHAUST_OFFSET: f64 = 16.0;
+
+    // Particles thrown out by each explosion
+    pub const MISSILE_SPARKS: u32 = 30;
+    pub const MISSILE_DEBRIS: u32 = 8;
+    pub const PLAYER_SPARKS: u32 = 60;
+    pub const PLAYER_DEBRIS: u32 = 16;
+
+    pub const PLAYER_EXHAUST: EmitterConfig = EmitterConfig {
+        rate: 60.0,
+        lifetime: (0.3, 0.5),
+        speed: (40.0, 80.0),
+        spread: 12.0,
+        size: &[(0.0, 7.0), (1.0, 2.0)],
+        color: [
+            &[(0.0, 1.0), (1.0, 0.9)],
+            &[(0.0, 0.9), (1.0, 0.3)],
+            &[(0.0, 0.5), (1.0, 0.1)],
+            &[(0.0, 0.8), (1.0, 0.0)],
+        ],
+    };
+
+    pub const MISSILE_EXHAUST: EmitterConfig = EmitterConfig {
+        rate: 40.0,
+        lifetime: (0.2, 0.4),
+        speed: (20.0, 50.0),
+        spread: 20.0,
+        size: &[(0.0, 5.0), (1.0, 1.0)],
+        color: [
+            &[(0.0, 0.9), (1.0, 0.5)],
+            &[(0.0, 0.9), (1.0, 0.5)],
+            &[(0.0, 0.9), (1.0, 0.5)],
+            &[(0.0, 0.6), (1.0, 0.0)],
+        ],
+    };
+
+    pub const SPARKS: EmitterConfig = EmitterConfig {
+        rate: 0.0,
+        lifetime: (0.3, 0.7),
+        speed: (150.0, 400.0),
+        spread: 180.0,
+        size: &[(0.0, 4.0), (1.0, 1.0)],
+        color: [
+            &[(0.0, 1.0), (1.0, 1.0)],
+            &[(0.0, 1.0), (0.5, 0.6), (1.0, 0.2)],
+            &[(0.0, 0.7), (0.5, 0.1), (1.0, 0.0)],
+            &[(0.0, 1.0), (1.0, 0.0)],
+        ],
+    };
+
+    pub const DEBRIS: EmitterConfig = EmitterConfig {
+        rate: 0.0,
+        lifetime: (1.0, 1.8),
+        speed: (40.0, 120.0),
+        spread: 180.0,
+        size: &[(0.0, 5.0), (1.0, 3.0)],
+        color: [
+            &[(0.0, 0.4), (1.0, 0.3)],
+            &[(0.0, 0.4), (1.0, 0.3)],
+            &[(0.0, 0.45), (1.0, 0.3)],
+            &[(0.0, 1.0), (0.7, 1.0), (1.0, 0.0)],
+        ],
+    };
+}
+
 pub mod offscreen_pointer {
+    pub const FILE: &str = "offscreen_pointer.png";
     pub const SCALE: f64 = 1.0;
     pub const OFFSET: f64 = 60.0;
 
     pub const OBJ_SCALE: f64 = 0.6;
+
+    // Missile pointers grow and pulse faster as the missile closes from far to near
+    pub const FAR_DISTANCE: f64 = 1200.0;
+    pub const NEAR_DISTANCE: f64 = 300.0;
+    pub const MAX_SCALE: f64 = 1.6;
+    pub const PULSE_AMOUNT: f64 = 0.15;
+    pub const MIN_PULSE_RATE: f64 = 0.5; // Pulses per second
+    pub const MAX_PULSE_RATE: f64 = 4.0;
+
+    // Time until impact for missiles and time left for pickups
+    pub const SHOW_LABELS: bool = true;
+    pub const LABEL_OFFSET: f64 = 50.0;
+    pub const LABEL_FONT_SIZE: u32 = 14;
+}
+
+pub mod radar {
+    use game::Point;
+    use layout::Anchor;
+
+    pub const ANCHOR: Anchor = Anchor::BottomRight;
+    pub const MARGIN: Point = Point { x: 20.0, y: 20.0 };
+    pub const RADIUS: f64 = 80.0;
+    // World distance shown at the edge of the radar
+    pub const RANGE: f64 = 1500.0;
+
+    pub const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+    pub const BORDER_COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
+    pub const BORDER_RADIUS: f64 = 1.0;
+    pub const PLAYER_COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
+    pub const HOMING_COLOR: [f32; 3] = [0.86, 0.2, 0.18];
+
+    pub const BLIP_RADIUS: f64 = 3.0;
+    // Opacity of blips at the edge of the range, closer ones are brighter
+    pub const MIN_BLIP_OPACITY: f32 = 0.25;
 }
 
 pub mod ui {
+    use game::Point;
+    use layout::Anchor;
+
+    pub const FONT: &str = "fonts/Gugi-Regular.ttf";
+
     pub const SHADOW_OFFSET: f64 = 2.0;
     pub const SHADOW_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
 
-    pub const SCORE_V_OFFSET: f64 = 120.0;
-    pub const SCORE_H_OFFSET: f64 = 95.0;
+    pub const SCORE_ANCHOR: Anchor = Anchor::TopLeft;
+    pub const SCORE_MARGIN: Point = Point { x: 95.0, y: 88.0 };
     pub const SCORE_COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
     pub const SCORE_FONT_SIZE: u32 = 32;
 
     pub mod game_over {
-        pub const GAME_OVER_V_OFFSET: f64 = 350.0;
-        pub const GAME_OVER_H_OFFSET: f64 = 455.0;
+        use game::Point;
+        use layout::Anchor;
+
+        pub const GAME_OVER_ANCHOR: Anchor = Anchor::Top;
+        pub const GAME_OVER_MARGIN: Point = Point { x: 0.0, y: 100.0 };
         pub const GAME_OVER_COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
         pub const GAME_OVER_FONT_SIZE: u32 = 72;
 
         pub const FADE_IN_LENGTH: f64 = 1.0;
 
-        pub const RESTART_V_OFFSET: f64 = 540.0;
-        pub const RESTART_H_OFFSET: f64 = 500.0;
+        pub const RESTART_ANCHOR: Anchor = Anchor::Bottom;
+        pub const RESTART_MARGIN: Point = Point { x: 0.0, y: 80.0 };
         pub const RESTART_COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
         pub const RESTART_FONT_SIZE: u32 = 24;
 
         pub const FADE_IN_OUT_LENGTH: f64 = 3.0;
+
+        // Stats panel, laid out in columns like the options screen
+        pub const STATS_ANCHOR: Anchor = Anchor::Top;
+        pub const STATS_MARGIN: Point = Point { x: 0.0, y: 216.0 };
+        pub const STATS_LINE_SPACING: f64 = 40.0;
+        pub const STATS_COLUMN_GAP: f64 = 40.0;
+        pub const STATS_FONT_SIZE: u32 = 24;
+        pub const STATS_COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
+        pub const STATS_BEST_COLOR: [f32; 4] = [0.5, 0.6, 0.5, 1.0];
+        pub const STATS_NEW_BEST_COLOR: [f32; 4] = [1.0, 0.84, 0.2, 1.0];
+
+        // Each line slides in a little after the one above it
+        pub const STATS_LINE_DELAY: f64 = 0.15;
+        pub const STATS_LINE_LENGTH: f64 = 0.5;
+        pub const STATS_SLIDE_DISTANCE: f64 = 60.0;
+    }
+
+    pub mod ship_select {
+        use game::Point;
+        use layout::Anchor;
+
+        pub const TITLE_ANCHOR: Anchor = Anchor::Top;
+        pub const TITLE_MARGIN: Point = Point { x: 0.0, y: 112.0 };
+        pub const TITLE_FONT_SIZE: u32 = 48;
+
+        // Ship stats and high scores are panels either side of the preview
+        pub const SHIP_ANCHOR: Anchor = Anchor::Left;
+        pub const SHIP_MARGIN: Point = Point { x: 120.0, y: 0.0 };
+        pub const HIGH_SCORES_ANCHOR: Anchor = Anchor::Right;
+        pub const HIGH_SCORES_MARGIN: Point = Point { x: 120.0, y: 0.0 };
+        pub const HIGH_SCORES_SHOWN: usize = 5;
+        pub const HEADING_SPACING: f64 = 40.0;
+        pub const LINE_SPACING: f64 = 30.0;
+        pub const NAME_FONT_SIZE: u32 = 32;
+        pub const STATS_FONT_SIZE: u32 = 20;
+
+        pub const PROMPT_ANCHOR: Anchor = Anchor::BottomLeft;
+        pub const PROMPT_MARGIN: Point = Point { x: 60.0, y: 40.0 };
+
+        pub const OPTIONS_ANCHOR: Anchor = Anchor::BottomRight;
+        pub const OPTIONS_MARGIN: Point = Point { x: 60.0, y: 40.0 };
+
+        pub const COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
+        pub const SELECTED_COLOR: [f32; 4] = [0.92, 0.99, 1.0, 1.0];
+        pub const PREVIEW_ANCHOR: Anchor = Anchor::Centre;
+        pub const PREVIEW_MARGIN: Point = Point { x: 0.0, y: 0.0 };
+        pub const PREVIEW_SCALE: f64 = 2.0;
+    }
+
+    pub mod options {
+        use game::Point;
+        use layout::Anchor;
+
+        pub const TITLE_ANCHOR: Anchor = Anchor::Top;
+        pub const TITLE_MARGIN: Point = Point { x: 0.0, y: 112.0 };
+        pub const TITLE_FONT_SIZE: u32 = 48;
+
+        pub const ITEMS_ANCHOR: Anchor = Anchor::Top;
+        pub const ITEMS_MARGIN: Point = Point { x: 0.0, y: 196.0 };
+        pub const COLUMN_GAP: f64 = 60.0;
+        pub const LINE_SPACING: f64 = 32.0;
+        pub const ITEMS_SHOWN: usize = 11;
+        pub const FONT_SIZE: u32 = 24;
+
+        pub const MESSAGE_ANCHOR: Anchor = Anchor::Bottom;
+        pub const MESSAGE_MARGIN: Point = Point { x: 0.0, y: 120.0 };
+        pub const MESSAGE_COLOR: [f32; 4] = [0.86, 0.2, 0.18, 1.0];
+
+        pub const PROMPT_ANCHOR: Anchor = Anchor::Bottom;
+        pub const PROMPT_MARGIN: Point = Point { x: 0.0, y: 70.0 };
+        pub const PROMPT_FONT_SIZE: u32 = 20;
+
+        pub const COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
+        pub const SELECTED_COLOR: [f32; 4] = [0.92, 0.99, 1.0, 1.0];
     }
+
+    pub mod pause {
+        use game::Point;
+        use layout::Anchor;
+
+        pub const ANCHOR: Anchor = Anchor::Centre;
+        pub const MARGIN: Point = Point { x: 0.0, y: -46.0 };
+        pub const COLOR: [f32; 4] = [0.17, 0.74, 0.18, 1.0];
+        pub const FONT_SIZE: u32 = 72;
+    }
+}
+
+pub mod high_score {
+    pub const FILE: &str = "highscores.txt";
+    pub const MAX_ENTRIES: usize = 10;
+}
+
+pub mod stats {
+    pub const FILE: &str = "stats.txt";
+    pub const COMBO_WINDOW: f64 = 2.0; // Seconds between hits to keep a combo going
 }
 
 pub mod background {
+    use background::{NebulaConfig, StarLayerConfig};
+
     pub const SCALE: f64 = 1.0;
 
-    pub const FILES: &'static [(&str, f64)] = &[
-        ("bkgd_0.png", 0.0),
-        ("bkgd_1.png", 0.01),
-        ("bkgd_2.png", 0.02),
-        ("bkgd_3.png", 0.03),
-        ("bkgd_4.png", 0.04),
-        ("bkgd_5.png", 0.05),
-        ("bkgd_6.png", 0.5),
-        ("bkgd_7.png", 1.0),
+    // Procedural sky, used instead of the images below when switched on
+    pub const PROCEDURAL: bool = false;
+    pub const SEED: Option<u64> = None; // A new sky every run
+    pub const TILE_SIZE: f64 = 1024.0;
+    pub const NEBULA_RINGS: u32 = 8;
+
+    pub const NEBULAE: &[NebulaConfig] = &[NebulaConfig {
+        factor: 0.01,
+        clouds: 3,
+        radius: (180.0, 420.0),
+        colors: &[
+            [0.35, 0.16, 0.55, 0.25],
+            [0.12, 0.28, 0.6, 0.25],
+            [0.6, 0.16, 0.3, 0.2],
+        ],
+    }];
+
+    pub const STAR_LAYERS: &[StarLayerConfig] = &[
+        StarLayerConfig {
+            factor: 0.02,
+            density: 90.0,
+            size: (1.0, 2.0),
+            opacity: (0.3, 0.7),
+            twinkle: 0.6,
+            twinkle_rate: (0.2, 0.8),
+            colors: &[[0.8, 0.85, 1.0], [1.0, 1.0, 1.0]],
+        },
+        StarLayerConfig {
+            factor: 0.05,
+            density: 40.0,
+            size: (2.0, 3.0),
+            opacity: (0.5, 0.9),
+            twinkle: 0.4,
+            twinkle_rate: (0.3, 1.2),
+            colors: &[[1.0, 1.0, 1.0], [1.0, 0.9, 0.75], [0.75, 0.85, 1.0]],
+        },
+        StarLayerConfig {
+            factor: 0.5,
+            density: 8.0,
+            size: (3.0, 5.0),
+            opacity: (0.8, 1.0),
+            twinkle: 0.2,
+            twinkle_rate: (0.5, 1.5),
+            colors: &[[1.0, 1.0, 1.0], [1.0, 0.95, 0.8]],
+        },
     ];
+
+    // Background themes and when each takes over, see the file for details
+    pub const THEMES: &str = "themes.txt";
+    pub const CROSSFADE: f64 = 3.0;
 }
@@ -0,0 +1,88 @@
+use game::Shape;
+use settings::ship::{balanced, fighter, interceptor};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ship {
+    pub name: &'static str,
+    pub clips: [&'static str; 3], // Banking left, level and banking right
+    pub speed: f64,
+    pub angular_velocity: f64, // Degrees per second
+    pub scale: f64,
+    pub hitbox: Shape,
+}
+
+pub const ROSTER: [Ship; 3] = [
+    Ship {
+        name: balanced::NAME,
+        clips: balanced::CLIPS,
+        speed: balanced::SPEED,
+        angular_velocity: balanced::ANGULAR_VELOCITY,
+        scale: balanced::SCALE,
+        hitbox: Shape::Polygon(balanced::HITBOX),
+    },
+    Ship {
+        name: interceptor::NAME,
+        clips: interceptor::CLIPS,
+        speed: interceptor::SPEED,
+        angular_velocity: interceptor::ANGULAR_VELOCITY,
+        scale: interceptor::SCALE,
+        hitbox: Shape::Polygon(interceptor::HITBOX),
+    },
+    Ship {
+        name: fighter::NAME,
+        clips: fighter::CLIPS,
+        speed: fighter::SPEED,
+        angular_velocity: fighter::ANGULAR_VELOCITY,
+        scale: fighter::SCALE,
+        hitbox: Shape::Polygon(fighter::HITBOX),
+    },
+];
+
+pub struct ShipSelect {
+    selected: usize,
+}
+
+impl ShipSelect {
+    pub fn new() -> ShipSelect {
+        ShipSelect { selected: 0 }
+    }
+
+    pub fn next(&mut self) -> () {
+        self.selected = (self.selected + 1) % ROSTER.len();
+    }
+
+    pub fn prev(&mut self) -> () {
+        self.selected = (self.selected + ROSTER.len() - 1) % ROSTER.len();
+    }
+
+    pub fn select(&mut self, idx: usize) -> () {
+        self.selected = idx % ROSTER.len();
+    }
+
+    pub fn ship(&self) -> Ship {
+        ROSTER[self.selected]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_wrap_selection() {
+        let mut select = ShipSelect::new();
+        select.prev();
+        assert_eq!(select.ship(), ROSTER[ROSTER.len() - 1]);
+        select.next();
+        assert_eq!(select.ship(), ROSTER[0]);
+    }
+
+    #[test]
+    fn it_should_give_each_ship_its_own_clips() {
+        for (i, a) in ROSTER.iter().enumerate() {
+            for b in ROSTER.iter().skip(i + 1) {
+                assert!(a.clips.iter().all(|clip| !b.clips.contains(clip)));
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use traits::Collides;
+
+// Buckets colliders into the cells of a uniform grid that their last moves overlap, so only
+// things sharing a cell need to be tested against each other
+pub struct SpatialHash {
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new<C: Collides>(objects: &[C], cell_size: f64) -> SpatialHash {
+        let cell = |coord: f64| (coord / cell_size).floor() as i64;
+        let mut cells = HashMap::new();
+
+        for (idx, obj) in objects.iter().enumerate() {
+            // Disabled colliders can't hit anything, so they're left out
+            if let Some([min_x, min_y, max_x, max_y]) = obj.get_collider().bounds() {
+                for x in cell(min_x)..=cell(max_x) {
+                    for y in cell(min_y)..=cell(max_y) {
+                        cells.entry((x, y)).or_insert_with(Vec::new).push(idx);
+                    }
+                }
+            }
+        }
+
+        SpatialHash { cells }
+    }
+
+    // Every pair of objects that share a cell, listed once each with the lower index first
+    pub fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        for indices in self.cells.values() {
+            // Indices went in in order, so each pair comes out lowest first
+            for (n, &i) in indices.iter().enumerate() {
+                pairs.extend(indices[(n + 1)..].iter().map(|&j| (i, j)));
+            }
+        }
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+}
+
+// Pairs of objects that touched during their last moves, in the same order as checking every
+// pair would find them. Until there are a lot of objects, checking every pair is quicker than
+// building the grid.
+pub fn colliding_pairs<C: Collides>(objects: &[C]) -> Vec<(usize, usize)> {
+    use settings::collision::{BROAD_PHASE_MIN, CELL_SIZE};
+
+    if objects.len() < BROAD_PHASE_MIN {
+        brute_force_pairs(objects)
+    } else {
+        grid_pairs(objects, CELL_SIZE)
+    }
+}
+
+fn grid_pairs<C: Collides>(objects: &[C], cell_size: f64) -> Vec<(usize, usize)> {
+    SpatialHash::new(objects, cell_size)
+        .candidate_pairs()
+        .into_iter()
+        .filter(|&(i, j)| objects[i].sweeps(&objects[j]))
+        .collect()
+}
+
+fn brute_force_pairs<C: Collides>(objects: &[C]) -> Vec<(usize, usize)> {
+    let mut pairs = vec![];
+    for (i, obj_i) in objects.iter().enumerate() {
+        for (j, obj_j) in objects.iter().enumerate().skip(i + 1) {
+            if obj_i.sweeps(obj_j) {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::{Collider, Point, Shape};
+
+    const TRIANGLE: &[Point] = &[
+        Point { x: 12.0, y: 0.0 },
+        Point { x: -6.0, y: 10.0 },
+        Point { x: -6.0, y: -10.0 },
+    ];
+
+    struct Body {
+        collider: Collider,
+    }
+
+    impl Collides for Body {
+        fn collides_with<C: Collides>(&self, other: &C) -> bool {
+            self.collider.collides_with(other.get_collider())
+        }
+
+        fn get_collider(&self) -> &Collider {
+            &self.collider
+        }
+    }
+
+    // Scatters bodies over a square, most of them moving, the same way every time. The shapes
+    // are used in turn, each turned to face some random way.
+    fn scatter(count: usize, size: f64, shapes: &[Shape]) -> Vec<Body> {
+        let mut state: u64 = 12345;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..count)
+            .map(|i| {
+                let pos = Point::new(next() * size - size / 2.0, next() * size - size / 2.0);
+                let shape = shapes[i % shapes.len()];
+                let mut collider = Collider::with_shape(pos, shape);
+                collider.set_rotation(next() * 360.0);
+                // Some bodies stay put, the rest move up to a few cells in the last frame
+                let reach = next() * 8.0 * shape.radius();
+                let travel = Point::new(next() - 0.5, next() - 0.5) * reach;
+                if next() < 0.75 {
+                    collider.move_to(pos + travel);
+                }
+                Body { collider }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_should_match_brute_force() {
+        let shapes = [
+            Shape::Circle(10.0),
+            Shape::Capsule {
+                half_length: 12.0,
+                r: 4.0,
+            },
+            Shape::Polygon(TRIANGLE),
+            Shape::Circle(5.0),
+        ];
+        for &(count, size) in [(0, 100.0), (1, 100.0), (50, 300.0), (200, 2000.0)].iter() {
+            let bodies = scatter(count, size, &shapes);
+            let expected = brute_force_pairs(&bodies);
+            for &cell_size in [8.0, 32.0, 64.0, 500.0].iter() {
+                assert_eq!(grid_pairs(&bodies, cell_size), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_find_pairs_across_cell_edges() {
+        let bodies = vec![
+            Body {
+                collider: Collider::new(Point::new(-1.0, 63.0), 1.5),
+            },
+            Body {
+                collider: Collider::new(Point::new(1.0, 65.0), 1.5),
+            },
+        ];
+        assert_eq!(grid_pairs(&bodies, 64.0), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn it_should_skip_disabled_colliders() {
+        let mut bodies = scatter(20, 50.0, &[Shape::Circle(10.0)]);
+        for body in bodies.iter_mut().step_by(2) {
+            body.collider.disable();
+        }
+        let pairs = grid_pairs(&bodies, 32.0);
+        assert!(pairs.iter().all(|&(i, j)| i % 2 == 1 && j % 2 == 1));
+        assert_eq!(pairs, brute_force_pairs(&bodies));
+    }
+
+    #[test]
+    fn it_should_use_the_grid_from_the_broad_phase_minimum() {
+        use settings::collision::BROAD_PHASE_MIN;
+        use settings::missile::COLLIDER;
+
+        // The game's missile cap never gets this far, so swarm-sized counts are checked here
+        let count = BROAD_PHASE_MIN;
+        let bodies = scatter(count, (count as f64).sqrt() * 150.0, &[COLLIDER]);
+        assert_eq!(colliding_pairs(&bodies), brute_force_pairs(&bodies));
+    }
+}
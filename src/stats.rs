@@ -0,0 +1,334 @@
+use missile::Missile;
+use player::Player;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Stat {
+    SurvivalTime,
+    MissilesDestroyed,
+    StarsCollected,
+    LongestCombo,
+    PeakMissiles,
+    ClosestNearMiss,
+    Distance,
+}
+
+pub const STATS: [Stat; 7] = [
+    Stat::SurvivalTime,
+    Stat::MissilesDestroyed,
+    Stat::StarsCollected,
+    Stat::LongestCombo,
+    Stat::PeakMissiles,
+    Stat::ClosestNearMiss,
+    Stat::Distance,
+];
+
+impl Stat {
+    // Name used in the personal bests file and the locale files
+    pub fn config_name(&self) -> &'static str {
+        match *self {
+            Stat::SurvivalTime => "survival_time",
+            Stat::MissilesDestroyed => "missiles_destroyed",
+            Stat::StarsCollected => "stars_collected",
+            Stat::LongestCombo => "longest_combo",
+            Stat::PeakMissiles => "peak_missiles",
+            Stat::ClosestNearMiss => "closest_near_miss",
+            Stat::Distance => "distance",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<Stat> {
+        STATS.iter().find(|s| s.config_name() == name).cloned()
+    }
+
+    // Near misses are better the closer they are, everything else the bigger the better
+    fn beats(&self, value: f64, best: f64) -> bool {
+        match *self {
+            Stat::ClosestNearMiss => value < best,
+            _ => value > best,
+        }
+    }
+}
+
+// What happened during one run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunStats {
+    pub survival_time: f64,
+    pub missiles_destroyed: u32,
+    pub stars_collected: u32,
+    pub longest_combo: u32,
+    pub peak_missiles: u32,
+    pub closest_near_miss: Option<f64>, // Gap between the edges of the hitboxes
+    pub distance: f64,
+    combo: u32,
+    combo_time_left: f64,
+}
+
+impl RunStats {
+    pub fn new() -> RunStats {
+        RunStats {
+            survival_time: 0.0,
+            missiles_destroyed: 0,
+            stars_collected: 0,
+            longest_combo: 0,
+            peak_missiles: 0,
+            closest_near_miss: None,
+            distance: 0.0,
+            combo: 0,
+            combo_time_left: 0.0,
+        }
+    }
+
+    // Takes the gaps of this frame's near misses, so only missiles that flew past without
+    // hitting count towards the closest one
+    pub fn update(
+        &mut self,
+        player: &Player,
+        missiles: &[Missile],
+        near_misses: &[f64],
+        dt: f64,
+    ) -> () {
+        if !player.is_active() {
+            return;
+        }
+
+        self.survival_time += dt;
+        self.distance += player.velocity().magnitude() * dt;
+
+        let active = missiles.iter().filter(|m| m.is_active()).count();
+        self.peak_missiles = self.peak_missiles.max(active as u32);
+        for &gap in near_misses.iter() {
+            self.closest_near_miss = Some(self.closest_near_miss.map_or(gap, |g| g.min(gap)));
+        }
+
+        self.combo_time_left -= dt;
+        if self.combo_time_left <= 0.0 {
+            self.combo = 0;
+        }
+    }
+
+    // Every missile destroyed and star collected adds to the combo, as long as each comes
+    // soon enough after the last. Missiles still crashing into each other after the player has
+    // been hit don't count.
+    pub fn add_hits(
+        &mut self,
+        player: &Player,
+        missiles_destroyed: u32,
+        stars_collected: u32,
+    ) -> () {
+        use settings::stats::COMBO_WINDOW;
+
+        if !player.is_active() {
+            return;
+        }
+
+        let hits = missiles_destroyed + stars_collected;
+        self.missiles_destroyed += missiles_destroyed;
+        self.stars_collected += stars_collected;
+        if hits > 0 {
+            self.combo += hits;
+            self.combo_time_left = COMBO_WINDOW;
+            self.longest_combo = self.longest_combo.max(self.combo);
+        }
+    }
+
+    pub fn value(&self, stat: Stat) -> Option<f64> {
+        match stat {
+            Stat::SurvivalTime => Some(self.survival_time),
+            Stat::MissilesDestroyed => Some(self.missiles_destroyed as f64),
+            Stat::StarsCollected => Some(self.stars_collected as f64),
+            Stat::LongestCombo => Some(self.longest_combo as f64),
+            Stat::PeakMissiles => Some(self.peak_missiles as f64),
+            Stat::ClosestNearMiss => self.closest_near_miss,
+            Stat::Distance => Some(self.distance),
+        }
+    }
+}
+
+// One line of the game over screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatLine {
+    pub stat: Stat,
+    pub value: Option<f64>,
+    pub best: Option<f64>, // Personal best before this run
+    pub new_best: bool,
+}
+
+pub struct PersonalBests {
+    path: PathBuf,
+    bests: Vec<(Stat, f64)>,
+}
+
+impl PersonalBests {
+    pub fn load(path: PathBuf) -> PersonalBests {
+        let bests = match fs::read_to_string(&path) {
+            Ok(contents) => parse_bests(&contents),
+            Err(_) => vec![],
+        };
+
+        PersonalBests { path, bests }
+    }
+
+    pub fn get(&self, stat: Stat) -> Option<f64> {
+        self.bests
+            .iter()
+            .find(|&&(s, _)| s == stat)
+            .map(|&(_, value)| value)
+    }
+
+    // Compares the run with the bests so far, then keeps whatever it beat
+    pub fn submit(&mut self, run: &RunStats) -> Vec<StatLine> {
+        let lines: Vec<StatLine> = STATS
+            .iter()
+            .map(|&stat| {
+                let value = run.value(stat);
+                let best = self.get(stat);
+                let new_best = match (value, best) {
+                    (Some(value), Some(best)) => stat.beats(value, best),
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                };
+                StatLine {
+                    stat,
+                    value,
+                    best,
+                    new_best,
+                }
+            })
+            .collect();
+
+        for line in lines.iter().filter(|l| l.new_best) {
+            let value = line.value.unwrap();
+            self.bests.retain(|&(s, _)| s != line.stat);
+            self.bests.push((line.stat, value));
+        }
+
+        lines
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(&self.path, format_bests(&self.bests))
+    }
+}
+
+// Each line is stored as "stat = value"
+fn parse_bests(contents: &str) -> Vec<(Stat, f64)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(2, '=');
+            let stat = Stat::from_config_name(fields.next()?.trim())?;
+            let value = fields.next()?.trim().parse::<f64>().ok()?;
+            Some((stat, value))
+        })
+        .collect()
+}
+
+fn format_bests(bests: &[(Stat, f64)]) -> String {
+    bests
+        .iter()
+        .map(|&(stat, value)| format!("{} = {}\n", stat.config_name(), value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use player::test_player;
+
+    fn player() -> Player {
+        let mut player = test_player();
+        player.reset();
+        player
+    }
+
+    fn bests(contents: &str) -> PersonalBests {
+        PersonalBests {
+            path: PathBuf::new(),
+            bests: parse_bests(contents),
+        }
+    }
+
+    #[test]
+    fn it_should_round_trip_bests() {
+        let bests = vec![(Stat::SurvivalTime, 61.5), (Stat::ClosestNearMiss, 2.25)];
+        assert_eq!(parse_bests(&format_bests(&bests)), bests);
+        assert_eq!(parse_bests("distance = far\nspeed = 3\n\n"), vec![]);
+    }
+
+    #[test]
+    fn it_should_chain_hits_into_combos() {
+        use settings::stats::COMBO_WINDOW;
+
+        let player = player();
+        let mut run = RunStats::new();
+        run.add_hits(&player, 2, 0);
+        run.update(&player, &[], &[], COMBO_WINDOW / 2.0);
+        run.add_hits(&player, 0, 1);
+        assert_eq!(run.longest_combo, 3);
+
+        // Let the combo run out before the next hit
+        run.update(&player, &[], &[], COMBO_WINDOW);
+        run.add_hits(&player, 1, 0);
+        assert_eq!(run.longest_combo, 3);
+        assert_eq!(run.missiles_destroyed, 3);
+        assert_eq!(run.stars_collected, 1);
+    }
+
+    #[test]
+    fn it_should_only_count_passes_as_near_misses() {
+        let player = player();
+        let mut run = RunStats::new();
+        run.update(&player, &[], &[], 0.1);
+        assert_eq!(run.closest_near_miss, None);
+
+        run.update(&player, &[], &[6.0, 2.5], 0.1);
+        run.update(&player, &[], &[3.0], 0.1);
+        assert_eq!(run.closest_near_miss, Some(2.5));
+    }
+
+    #[test]
+    fn it_should_ignore_hits_after_death() {
+        let mut player = player();
+        let mut run = RunStats::new();
+        player.explode();
+        run.add_hits(&player, 3, 1);
+        run.update(&player, &[], &[1.0], 0.1);
+        assert_eq!(run, RunStats::new());
+    }
+
+    #[test]
+    fn it_should_compare_run_with_bests() {
+        let mut bests = bests("survival_time = 30\nclosest_near_miss = 5\nstars_collected = 4\n");
+        let player = player();
+        let mut run = RunStats::new();
+        run.update(&player, &[], &[8.0], 45.0);
+        run.add_hits(&player, 0, 4);
+
+        let lines = bests.submit(&run);
+        let line = |stat| lines.iter().find(|l| l.stat == stat).unwrap().clone();
+        assert!(line(Stat::SurvivalTime).new_best);
+        assert_eq!(line(Stat::SurvivalTime).best, Some(30.0));
+        assert!(!line(Stat::ClosestNearMiss).new_best);
+        assert!(!line(Stat::StarsCollected).new_best);
+        assert!(line(Stat::Distance).new_best);
+
+        assert_eq!(bests.get(Stat::SurvivalTime), Some(45.0));
+        assert_eq!(bests.get(Stat::ClosestNearMiss), Some(5.0));
+    }
+
+    #[test]
+    fn it_should_count_closer_near_misses_as_better() {
+        let mut bests = bests("closest_near_miss = 5\n");
+        let mut run = RunStats::new();
+        run.update(&player(), &[], &[1.5], 0.1);
+        bests.submit(&run);
+        assert_eq!(bests.get(Stat::ClosestNearMiss), Some(1.5));
+
+        // No missile ever came near, so there's nothing to compare
+        let lines = bests.submit(&RunStats::new());
+        assert!(!lines.iter().any(|l| l.stat == Stat::ClosestNearMiss && l.new_best));
+    }
+}
@@ -4,4 +4,14 @@ pub trait Collides {
     fn collides_with<C: Collides>(&self, other: &C) -> bool;
 
     fn get_collider(&self) -> &Collider;
+
+    // Whether the two touched anywhere along their last moves
+    fn sweeps<C: Collides>(&self, other: &C) -> bool {
+        self.get_collider().sweeps(other.get_collider())
+    }
+
+    // Whether the two come within the margin of touching, or overlap
+    fn is_near<C: Collides>(&self, other: &C, margin: f64) -> bool {
+        self.get_collider().is_near(other.get_collider(), margin)
+    }
 }
@@ -82,25 +82,7 @@ impl Tween {
 
     pub fn get_val(&self) -> f64 {
         if self.playing {
-            let total_frac = self.duration / self.length;
-
-            // Find the index of the first keyframe that is greater than total_frac
-            let maybe_idx = self.keyframes
-                .iter()
-                .position(|&keyframe| keyframe.0 >= total_frac);
-
-            match maybe_idx {
-                Some(idx) if idx > 0 => {
-                    let from = self.keyframes[idx - 1];
-                    let to = self.keyframes[idx];
-                    let frac = (total_frac - from.0) / (to.0 - from.0);
-
-                    // Calculate the current interpolated value using the desired easing
-                    (self.easing_fn)(frac) * (to.1 - from.1) + from.1
-                }
-                Some(_) => self.keyframes[0].1,
-                None => self.keyframes.last().unwrap().1,
-            }
+            sample(&self.keyframes, self.duration / self.length, self.easing_fn)
         } else {
             // Keyframes must have at least one value or else it will panic
             self.keyframes.last().unwrap().0
@@ -122,6 +104,31 @@ impl Tween {
     }
 }
 
+// Value of the keyframes a fraction of the way through, for things that track their own time
+pub fn sample_keyframes(keyframes: &[KeyFrame], total_frac: f64) -> f64 {
+    sample(keyframes, total_frac, linear)
+}
+
+fn sample(keyframes: &[KeyFrame], total_frac: f64, easing_fn: EasingFn) -> f64 {
+    // Find the index of the first keyframe that is greater than total_frac
+    let maybe_idx = keyframes
+        .iter()
+        .position(|&keyframe| keyframe.0 >= total_frac);
+
+    match maybe_idx {
+        Some(idx) if idx > 0 => {
+            let from = keyframes[idx - 1];
+            let to = keyframes[idx];
+            let frac = (total_frac - from.0) / (to.0 - from.0);
+
+            // Calculate the current interpolated value using the desired easing
+            easing_fn(frac) * (to.1 - from.1) + from.1
+        }
+        Some(_) => keyframes[0].1,
+        None => keyframes.last().unwrap().1,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,6 +162,15 @@ mod tests {
         test_easing_function(input, expected_output, ease_out);
     }
 
+    #[test]
+    fn it_should_sample_keyframes() {
+        let keyframes = [(0.0, 10.0), (0.5, 20.0), (1.0, 0.0)];
+        assert_approx_eq!(sample_keyframes(&keyframes, 0.0), 10.0);
+        assert_approx_eq!(sample_keyframes(&keyframes, 0.25), 15.0);
+        assert_approx_eq!(sample_keyframes(&keyframes, 0.75), 10.0);
+        assert_approx_eq!(sample_keyframes(&keyframes, 2.0), 0.0);
+    }
+
     #[test]
     fn it_should_calcualate_correct_ease_in_out() {
         let input = vec![0.0, 0.25, 0.5, 0.75, 1.0];
@@ -1,10 +1,48 @@
 extern crate piston_window;
 
+use animation::{Animation, Clips};
+use config::Config;
+use controls::{key_label, Bindings, GameAction};
+use fonts::Fonts;
 use game::*;
+use highscores::HighScore;
+use layout::{place, place_columns, place_text, Anchor};
+use locale::Locale;
+use options::Options;
 use piston_window::*;
 use player;
 use settings::ui::*;
+use ship::{self, Ship};
+use stats::{Stat, StatLine, STATS};
 use tween::*;
+use viewport::Viewport;
+
+// Everything UI text and previews are drawn with, gathered once per frame
+pub struct Canvas<'a> {
+    locale: &'a Locale,
+    font: &'a mut Fonts,
+    clips: &'a Clips,
+    area: Point, // The logical screen that text is anchored within
+    c: piston_window::Context,
+}
+
+impl<'a> Canvas<'a> {
+    pub fn new(
+        locale: &'a Locale,
+        font: &'a mut Fonts,
+        clips: &'a Clips,
+        viewport: &Viewport,
+        c: piston_window::Context,
+    ) -> Canvas<'a> {
+        Canvas {
+            locale,
+            font,
+            clips,
+            area: Point::new(viewport.width(), viewport.height()),
+            c,
+        }
+    }
+}
 
 #[derive(Copy, Clone, PartialEq)]
 enum State {
@@ -16,10 +54,25 @@ pub struct UI {
     state: State,
     game_over_tween: Tween,
     restart_tween: Tween,
+    stat_tweens: Vec<Tween>,
+    previews: Vec<(Ship, Animation)>, // Each ship flying level, for the ship select screen
 }
 
 impl UI {
-    pub fn new() -> Self {
+    pub fn new(clips: &Clips) -> Self {
+        use settings::ui::ship_select::PREVIEW_SCALE;
+
+        let previews = ship::ROSTER
+            .iter()
+            .map(|&ship| {
+                let scale = PREVIEW_SCALE * ship.scale;
+                let mut preview = Animation::new(clips.get(ship.clips[1]), scale);
+                preview.set_rotation(-90.0);
+                preview.play();
+                (ship, preview)
+            })
+            .collect();
+
         UI {
             state: State::GameActive,
             game_over_tween: Tween::new(
@@ -34,6 +87,8 @@ impl UI {
                 Easing::EaseInOut,
                 true,
             ),
+            stat_tweens: (0..STATS.len()).map(stat_tween).collect(),
+            previews,
         }
     }
 
@@ -45,9 +100,12 @@ impl UI {
                 self.go_to_game_over();
                 self.game_over_tween.update(dt);
 
-                // Only start showing restart text after game over has appeared
+                // Only start showing stats and restart text after game over has appeared
                 if !self.game_over_tween.is_playing() {
                     self.restart_tween.update(dt);
+                    for tween in self.stat_tweens.iter_mut() {
+                        tween.update(dt);
+                    }
                 }
             }
         }
@@ -56,18 +114,270 @@ impl UI {
     pub fn draw(
         &self,
         score: Score,
-        font: &mut Glyphs,
-        c: piston_window::Context,
+        stats: &[StatLine],
+        bindings: &Bindings,
+        scan: Option<usize>,
+        canvas: &mut Canvas,
         g: &mut G2d,
     ) -> () {
-        draw_score(score, font, c, g);
+        let locale = canvas.locale;
+        draw_anchored_text(
+            &locale.format("score", &[("score", &locale.number(score))]),
+            SCORE_ANCHOR,
+            SCORE_MARGIN,
+            SCORE_COLOR,
+            SCORE_FONT_SIZE,
+            canvas,
+            g,
+        );
         match self.state {
             State::GameActive => {}
             State::GameOver => {
-                draw_game_over_text(font, self.game_over_tween.get_val(), c, g);
-                draw_restart_text(font, self.restart_tween.get_val(), c, g);
+                use settings::ui::game_over::*;
+
+                draw_anchored_text(
+                    locale.get("game_over"),
+                    GAME_OVER_ANCHOR,
+                    GAME_OVER_MARGIN,
+                    set_opacity(GAME_OVER_COLOR, self.game_over_tween.get_val() as f32),
+                    GAME_OVER_FONT_SIZE,
+                    canvas,
+                    g,
+                );
+
+                let texts: Vec<StatTexts> = stats.iter().map(|l| stat_texts(l, locale)).collect();
+                let font = &mut *canvas.font;
+                let widths = [
+                    widest(texts.iter().map(|t| &t.label[..]), STATS_FONT_SIZE, font),
+                    widest(texts.iter().map(|t| &t.value[..]), STATS_FONT_SIZE, font),
+                    widest(
+                        texts.iter().filter_map(|t| t.best.as_ref()).map(|b| &b.0[..]),
+                        STATS_FONT_SIZE,
+                        font,
+                    ),
+                ];
+                let height = STATS_LINE_SPACING * texts.len() as f64;
+                let columns = place_columns(
+                    &widths,
+                    STATS_COLUMN_GAP,
+                    height,
+                    STATS_ANCHOR,
+                    STATS_MARGIN,
+                    canvas.area,
+                );
+                for (i, (text, tween)) in texts.iter().zip(self.stat_tweens.iter()).enumerate() {
+                    let v_offset = i as f64 * STATS_LINE_SPACING + STATS_FONT_SIZE as f64;
+                    draw_stat_line(text, &columns, v_offset, tween.get_val(), canvas, g);
+                }
+
+                let key = key_label(bindings.key(GameAction::Restart));
+                let ships = key_label(bindings.key(GameAction::ChangeShip));
+                let prompt = match scan {
+                    Some(0) => locale.format("scan.play_again", &[("key", &key)]),
+                    Some(_) => locale.format("scan.change_ship", &[("key", &key)]),
+                    None => locale.format("restart", &[("key", &key), ("ships", &ships)]),
+                };
+                draw_anchored_text(
+                    &prompt,
+                    RESTART_ANCHOR,
+                    RESTART_MARGIN,
+                    set_opacity(RESTART_COLOR, self.restart_tween.get_val() as f32),
+                    RESTART_FONT_SIZE,
+                    canvas,
+                    g,
+                );
+            }
+        }
+    }
+
+    pub fn draw_paused(&self, canvas: &mut Canvas, g: &mut G2d) -> () {
+        use settings::ui::pause::*;
+
+        let text = canvas.locale.get("paused");
+        draw_anchored_text(text, ANCHOR, MARGIN, COLOR, FONT_SIZE, canvas, g);
+    }
+
+    pub fn draw_options(
+        &self,
+        options: &Options,
+        config: &Config,
+        canvas: &mut Canvas,
+        g: &mut G2d,
+    ) -> () {
+        use settings::ui::options::*;
+
+        let locale = canvas.locale;
+        draw_anchored_text(
+            locale.get("options.title"),
+            TITLE_ANCHOR,
+            TITLE_MARGIN,
+            COLOR,
+            TITLE_FONT_SIZE,
+            canvas,
+            g,
+        );
+
+        // Scroll the list to keep the selected line in view
+        let lines = options.lines(config, locale);
+        let first = options
+            .selected()
+            .saturating_sub(ITEMS_SHOWN / 2)
+            .min(lines.len().saturating_sub(ITEMS_SHOWN));
+
+        // Sized for every line rather than just those shown, so the columns don't shift on scroll
+        let widths = [
+            widest(lines.iter().map(|l| &l.0[..]), FONT_SIZE, canvas.font),
+            widest(lines.iter().map(|l| &l.1[..]), FONT_SIZE, canvas.font),
+        ];
+        let height = LINE_SPACING * ITEMS_SHOWN.min(lines.len()) as f64;
+        let area = canvas.area;
+        let columns = place_columns(&widths, COLUMN_GAP, height, ITEMS_ANCHOR, ITEMS_MARGIN, area);
+        for (i, (label, value)) in lines.iter().enumerate().skip(first).take(ITEMS_SHOWN) {
+            let color = if i == options.selected() {
+                SELECTED_COLOR
+            } else {
+                COLOR
+            };
+            let v_offset = (i - first) as f64 * LINE_SPACING + FONT_SIZE as f64;
+            for (text, column) in [label, value].iter().zip(columns.iter()) {
+                let pos = Point::new(column.x, column.y + v_offset);
+                draw_text_with_shadow(text, pos, color, FONT_SIZE, canvas, g);
             }
         }
+
+        if let Some(message) = options.message(locale) {
+            draw_anchored_text(
+                &message,
+                MESSAGE_ANCHOR,
+                MESSAGE_MARGIN,
+                MESSAGE_COLOR,
+                FONT_SIZE,
+                canvas,
+                g,
+            );
+        }
+
+        let prompt = if options.is_rebinding() {
+            let cancel = key_label(config.bindings.key(GameAction::Quit));
+            locale.format("options.rebinding", &[("cancel", &cancel)])
+        } else if config.input_mode.is_one_button() {
+            let key = key_label(config.bindings.key(GameAction::Restart));
+            locale.format("scan.change", &[("key", &key)])
+        } else {
+            let back = key_label(config.bindings.key(GameAction::Options));
+            locale.format("options.prompt", &[("back", &back)])
+        };
+        draw_anchored_text(
+            &prompt,
+            PROMPT_ANCHOR,
+            PROMPT_MARGIN,
+            COLOR,
+            PROMPT_FONT_SIZE,
+            canvas,
+            g,
+        );
+    }
+
+    pub fn draw_ship_select(
+        &self,
+        ship: Ship,
+        high_scores: &[&HighScore],
+        config: &Config,
+        scan: Option<usize>,
+        canvas: &mut Canvas,
+        g: &mut G2d,
+    ) -> () {
+        use settings::ui::ship_select::*;
+
+        let (locale, bindings) = (canvas.locale, &config.bindings);
+        draw_anchored_text(
+            locale.get("ship_select.title"),
+            TITLE_ANCHOR,
+            TITLE_MARGIN,
+            COLOR,
+            TITLE_FONT_SIZE,
+            canvas,
+            g,
+        );
+        // One-button players step through the ships and then the options
+        let launch = key_label(bindings.key(GameAction::Restart));
+        let on_options = scan == Some(ship::ROSTER.len());
+        let prompt = match scan {
+            Some(_) if on_options => locale.format("scan.options", &[("key", &launch)]),
+            Some(_) => locale.format("scan.launch", &[("key", &launch)]),
+            None => locale.format(
+                "ship_select.prompt",
+                &[
+                    ("left", &key_label(bindings.key(GameAction::TurnLeft))),
+                    ("right", &key_label(bindings.key(GameAction::TurnRight))),
+                    ("launch", &launch),
+                ],
+            ),
+        };
+        draw_anchored_text(
+            &prompt,
+            PROMPT_ANCHOR,
+            PROMPT_MARGIN,
+            COLOR,
+            STATS_FONT_SIZE,
+            canvas,
+            g,
+        );
+        let key = key_label(bindings.key(GameAction::Options));
+        draw_anchored_text(
+            &locale.format("ship_select.options", &[("key", &key)]),
+            OPTIONS_ANCHOR,
+            OPTIONS_MARGIN,
+            if on_options { SELECTED_COLOR } else { COLOR },
+            STATS_FONT_SIZE,
+            canvas,
+            g,
+        );
+
+        // Draw ship preview
+        if let Some((_, preview)) = self.previews.iter().find(|(s, _)| *s == ship) {
+            let pos = place(PREVIEW_ANCHOR, PREVIEW_MARGIN, Point::new(0.0, 0.0), canvas.area);
+            preview.draw_at_pos(canvas.clips, pos, 1.0, canvas.c, g);
+        }
+
+        // Draw ship stats
+        let stats = [
+            ("ship_select.speed", ship.speed),
+            ("ship_select.turn_rate", ship.angular_velocity),
+            ("ship_select.hitbox", ship.hitbox.radius().round()),
+        ];
+        let lines: Vec<String> = stats
+            .iter()
+            .map(|&(key, value)| locale.format(key, &[("value", &value.to_string())]))
+            .collect();
+        draw_panel(
+            ship.name,
+            &lines,
+            stats.len(),
+            SHIP_ANCHOR,
+            SHIP_MARGIN,
+            canvas,
+            g,
+        );
+
+        // Draw high scores
+        let speed = config.game_speed.to_string();
+        let heading = locale.format("ship_select.high_scores", &[("speed", &speed)]);
+        let lines: Vec<String> = high_scores
+            .iter()
+            .take(HIGH_SCORES_SHOWN)
+            .enumerate()
+            .map(|(i, entry)| format!("{}. {}  {}", i + 1, locale.number(entry.score), entry.ship))
+            .collect();
+        draw_panel(
+            &heading,
+            &lines,
+            HIGH_SCORES_SHOWN,
+            HIGH_SCORES_ANCHOR,
+            HIGH_SCORES_MARGIN,
+            canvas,
+            g,
+        );
     }
 
     fn go_to_game_over(&mut self) -> () {
@@ -76,6 +386,9 @@ impl UI {
             State::GameActive => {
                 self.game_over_tween.reset();
                 self.restart_tween.reset();
+                for tween in self.stat_tweens.iter_mut() {
+                    tween.reset();
+                }
                 self.state = State::GameOver;
             }
         }
@@ -87,116 +400,168 @@ impl UI {
             State::GameOver => {
                 self.game_over_tween.stop();
                 self.restart_tween.stop();
+                for tween in self.stat_tweens.iter_mut() {
+                    tween.stop();
+                }
                 self.state = State::GameActive;
             }
         }
     }
 }
 
-fn draw_text(
+fn widest<'a, I: Iterator<Item = &'a str>>(texts: I, font_size: u32, font: &mut Fonts) -> f64 {
+    texts.map(|text| font.width(font_size, text)).fold(0.0, f64::max)
+}
+
+// A heading over a list of ship select lines, anchored as one block. The block is sized for
+// the given number of rows so it doesn't move when fewer lines are shown.
+fn draw_panel(
+    heading: &str,
+    lines: &[String],
+    rows: usize,
+    anchor: Anchor,
+    margin: Point,
+    canvas: &mut Canvas,
+    g: &mut G2d,
+) -> () {
+    use settings::ui::ship_select::*;
+
+    let width = canvas.font.width(NAME_FONT_SIZE, heading)
+        .max(widest(lines.iter().map(|l| &l[..]), STATS_FONT_SIZE, canvas.font));
+    let heading_height = NAME_FONT_SIZE as f64;
+    let height = heading_height + HEADING_SPACING + rows.saturating_sub(1) as f64 * LINE_SPACING;
+    let pos = place(anchor, margin, Point::new(width, height), canvas.area);
+
+    let heading_pos = Point::new(pos.x, pos.y + heading_height);
+    draw_text_with_shadow(heading, heading_pos, COLOR, NAME_FONT_SIZE, canvas, g);
+    for (i, line) in lines.iter().enumerate() {
+        let v_offset = HEADING_SPACING + i as f64 * LINE_SPACING;
+        let line_pos = heading_pos + Point::new(0.0, v_offset);
+        draw_text_with_shadow(line, line_pos, COLOR, STATS_FONT_SIZE, canvas, g);
+    }
+}
+
+fn draw_text_with_shadow(
     text: &str,
-    transform: [[f64; 3]; 2],
-    font: &mut Glyphs,
+    pos: Point,
     color: [f32; 4],
     font_size: u32,
-    c: piston_window::Context,
+    canvas: &mut Canvas,
     g: &mut G2d,
 ) -> () {
-    text::Text::new_color(color, font_size)
-        .round()
-        .draw(text, font, &c.draw_state, transform, g)
-        .unwrap();
-}
+    let c = canvas.c;
 
-fn draw_score(score: Score, font: &mut Glyphs, c: piston_window::Context, g: &mut G2d) -> () {
-    let text = &format!("Score: {}", score);
+    // Draw shadow, fading along with the text
+    let transform = c.transform.trans(SHADOW_OFFSET + pos.x, SHADOW_OFFSET + pos.y);
+    let shadow_color = set_opacity(SHADOW_COLOR, color[3]);
+    canvas.font.draw(text, shadow_color, font_size, transform, c, g);
 
-    // Draw shadow
-    let transform = c.transform.trans(
-        SHADOW_OFFSET + SCORE_H_OFFSET,
-        SHADOW_OFFSET + SCORE_V_OFFSET,
-    );
-    draw_text(text, transform, font, SHADOW_COLOR, SCORE_FONT_SIZE, c, g);
-
-    // Draw score
-    let transform = c.transform.trans(SCORE_H_OFFSET, SCORE_V_OFFSET);
-    draw_text(text, transform, font, SCORE_COLOR, SCORE_FONT_SIZE, c, g);
+    // Draw text
+    let transform = c.transform.trans(pos.x, pos.y);
+    canvas.font.draw(text, color, font_size, transform, c, g);
 }
 
-fn draw_game_over_text(
-    font: &mut Glyphs,
-    opacity: f64,
-    c: piston_window::Context,
+// Measures the text so it lines up with its anchor on the logical screen, whatever its length
+fn draw_anchored_text(
+    text: &str,
+    anchor: Anchor,
+    margin: Point,
+    color: [f32; 4],
+    font_size: u32,
+    canvas: &mut Canvas,
     g: &mut G2d,
 ) -> () {
+    let pos = place_text(text, anchor, margin, font_size, canvas.font, canvas.area);
+    draw_text_with_shadow(text, pos, color, font_size, canvas, g);
+}
+
+// Lines of the stats panel share one length, each waiting a little longer than the last
+fn stat_tween(line: usize) -> Tween {
+    use settings::ui::game_over::{STATS_LINE_DELAY, STATS_LINE_LENGTH};
+
+    let length = STATS_LINE_DELAY * (STATS.len() - 1) as f64 + STATS_LINE_LENGTH;
+    let start = STATS_LINE_DELAY * line as f64 / length;
+    let end = start + STATS_LINE_LENGTH / length;
+
+    let mut keyframes = vec![(0.0, 0.0)];
+    if start > 0.0 {
+        keyframes.push((start, 0.0));
+    }
+    keyframes.push((end, 1.0));
+    if end < 1.0 {
+        keyframes.push((1.0, 1.0));
+    }
+    Tween::new(keyframes, length, Easing::EaseOut, false)
+}
+
+struct StatTexts {
+    label: String,
+    value: String,
+    best: Option<(String, [f32; 4])>,
+}
+
+fn stat_texts(line: &StatLine, locale: &Locale) -> StatTexts {
     use settings::ui::game_over::*;
 
-    let text = "Game Over";
-
-    // Draw shadow
-    let transform = c.transform.trans(
-        SHADOW_OFFSET + GAME_OVER_H_OFFSET,
-        SHADOW_OFFSET + GAME_OVER_V_OFFSET,
-    );
-    draw_text(
-        text,
-        transform,
-        font,
-        set_opacity(SHADOW_COLOR, opacity as f32),
-        GAME_OVER_FONT_SIZE,
-        c,
-        g,
-    );
-
-    // Draw game over
-    let transform = c.transform.trans(GAME_OVER_H_OFFSET, GAME_OVER_V_OFFSET);
-    draw_text(
-        text,
-        transform,
-        font,
-        set_opacity(GAME_OVER_COLOR, opacity as f32),
-        GAME_OVER_FONT_SIZE,
-        c,
-        g,
-    );
+    let label = locale.get(&format!("stats.{}", line.stat.config_name())).to_owned();
+    let value = match line.value {
+        Some(value) => stat_text(line.stat, value, locale),
+        None => "-".to_owned(),
+    };
+    let best = match line.best {
+        _ if line.new_best => Some((locale.get("stats.new_best").to_owned(), STATS_NEW_BEST_COLOR)),
+        Some(best) => {
+            let best = stat_text(line.stat, best, locale);
+            Some((locale.format("stats.best", &[("value", &best)]), STATS_BEST_COLOR))
+        }
+        None => None,
+    };
+    StatTexts { label, value, best }
 }
 
-fn draw_restart_text(
-    font: &mut Glyphs,
-    opacity: f64,
-    c: piston_window::Context,
+// Slides in from the left and fades in as progress goes from 0 to 1
+fn draw_stat_line(
+    texts: &StatTexts,
+    columns: &[Point],
+    v_offset: f64,
+    progress: f64,
+    canvas: &mut Canvas,
     g: &mut G2d,
 ) -> () {
     use settings::ui::game_over::*;
 
-    let text = "Press SPACE to play again";
-
-    // Draw shadow
-    let transform = c.transform.trans(
-        SHADOW_OFFSET + RESTART_H_OFFSET,
-        SHADOW_OFFSET + RESTART_V_OFFSET,
-    );
-    draw_text(
-        text,
-        transform,
-        font,
-        set_opacity(SHADOW_COLOR, opacity as f32),
-        RESTART_FONT_SIZE,
-        c,
-        g,
-    );
-
-    // Draw game over
-    let transform = c.transform.trans(RESTART_H_OFFSET, RESTART_V_OFFSET);
-    draw_text(
-        text,
-        transform,
-        font,
-        set_opacity(RESTART_COLOR, opacity as f32),
-        RESTART_FONT_SIZE,
-        c,
-        g,
-    );
+    let opacity = progress as f32;
+    let slide = (1.0 - progress) * -STATS_SLIDE_DISTANCE;
+    let color = set_opacity(STATS_COLOR, opacity);
+    let best = texts.best.as_ref().map(|&(ref text, color)| (text, set_opacity(color, opacity)));
+    let cells = [
+        Some((&texts.label, color)),
+        Some((&texts.value, color)),
+        best,
+    ];
+
+    for (cell, column) in cells.iter().zip(columns.iter()) {
+        if let Some((text, color)) = *cell {
+            let pos = Point::new(column.x + slide, column.y + v_offset);
+            draw_text_with_shadow(text, pos, color, STATS_FONT_SIZE, canvas, g);
+        }
+    }
+}
+
+fn stat_text(stat: Stat, value: f64, locale: &Locale) -> String {
+    match stat {
+        Stat::SurvivalTime => {
+            locale.format("stats.seconds", &[("value", &locale.decimal(value, 1))])
+        }
+        Stat::ClosestNearMiss => {
+            locale.format("stats.pixels", &[("value", &locale.decimal(value, 1))])
+        }
+        Stat::Distance => locale.format(
+            "stats.pixels",
+            &[("value", &locale.number(value.round() as Score))],
+        ),
+        _ => locale.number(value as Score),
+    }
 }
 
 fn set_opacity(color: [f32; 4], opacity: f32) -> [f32; 4] {
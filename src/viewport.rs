@@ -0,0 +1,133 @@
+extern crate piston_window;
+
+use game::*;
+use piston_window::*;
+
+// Maps the window onto a logical screen of fixed height. The logical width follows the window's
+// aspect ratio within limits, and anything beyond those limits is letterboxed.
+#[derive(Debug, Copy, Clone)]
+pub struct Viewport {
+    window: Point,
+    size: Point,
+}
+
+impl Viewport {
+    pub fn new(window_width: f64, window_height: f64) -> Viewport {
+        let mut viewport = Viewport {
+            window: Point::new(0.0, 0.0),
+            size: Point::new(0.0, 0.0),
+        };
+        viewport.resize(window_width, window_height);
+        viewport
+    }
+
+    pub fn resize(&mut self, window_width: f64, window_height: f64) -> () {
+        use settings::window::{LOGICAL_HEIGHT, MAX_ASPECT, MIN_ASPECT};
+
+        // Guard against a minimised window
+        let window_width = window_width.max(1.0);
+        let window_height = window_height.max(1.0);
+
+        let aspect = (window_width / window_height).clamp(MIN_ASPECT, MAX_ASPECT);
+        self.window = Point::new(window_width, window_height);
+        self.size = Point::new(LOGICAL_HEIGHT * aspect, LOGICAL_HEIGHT);
+    }
+
+    pub fn width(&self) -> f64 {
+        self.size.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.size.y
+    }
+
+    pub fn centre(&self) -> Point {
+        self.size / 2.0
+    }
+
+    // Window pixels per logical pixel
+    pub fn scale(&self) -> f64 {
+        (self.window.x / self.size.x).min(self.window.y / self.size.y)
+    }
+
+    // Top left corner of the logical screen in the window
+    pub fn offset(&self) -> Point {
+        (self.window - self.size * self.scale()) / 2.0
+    }
+
+    // Lets everything draw in logical coordinates
+    pub fn transform(&self, c: Context) -> Context {
+        let offset = self.offset();
+        let mut c = c;
+        c.transform = c.transform
+            .trans(offset.x, offset.y)
+            .scale(self.scale(), self.scale());
+        c
+    }
+
+    pub fn to_window(self, pos: Point) -> Point {
+        pos * self.scale() + self.offset()
+    }
+
+    // Black bars covering whatever of the window is outside the logical screen
+    pub fn draw_letterbox(&self, c: Context, g: &mut G2d) -> () {
+        use settings::window::LETTERBOX_COLOR;
+
+        let offset = self.offset();
+        let inner = self.size * self.scale();
+
+        if offset.x > 0.0 {
+            rectangle(LETTERBOX_COLOR, [0.0, 0.0, offset.x, self.window.y], c.transform, g);
+            rectangle(
+                LETTERBOX_COLOR,
+                [offset.x + inner.x, 0.0, offset.x, self.window.y],
+                c.transform,
+                g,
+            );
+        }
+        if offset.y > 0.0 {
+            rectangle(LETTERBOX_COLOR, [0.0, 0.0, self.window.x, offset.y], c.transform, g);
+            rectangle(
+                LETTERBOX_COLOR,
+                [0.0, offset.y + inner.y, self.window.x, offset.y],
+                c.transform,
+                g,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use settings::window::{LOGICAL_HEIGHT, MAX_ASPECT};
+
+    #[test]
+    fn it_should_follow_window_aspect_ratio() {
+        let viewport = Viewport::new(1600.0, 900.0);
+        assert_approx_eq!(viewport.height(), LOGICAL_HEIGHT);
+        assert_approx_eq!(viewport.width(), LOGICAL_HEIGHT * 16.0 / 9.0);
+        assert_approx_eq!(viewport.offset().x, 0.0);
+        assert_approx_eq!(viewport.offset().y, 0.0);
+    }
+
+    #[test]
+    fn it_should_letterbox_very_wide_windows() {
+        let viewport = Viewport::new(4000.0, 1000.0);
+        let scale = 1000.0 / LOGICAL_HEIGHT;
+        assert_approx_eq!(viewport.width(), LOGICAL_HEIGHT * MAX_ASPECT);
+        assert_approx_eq!(viewport.scale(), scale);
+        assert_approx_eq!(
+            viewport.offset().x,
+            (4000.0 - LOGICAL_HEIGHT * MAX_ASPECT * scale) / 2.0
+        );
+    }
+
+    #[test]
+    fn it_should_map_logical_centre_to_window_centre() {
+        let viewport = Viewport::new(1000.0, 1000.0);
+        let centre = viewport.to_window(viewport.centre());
+        assert_approx_eq!(centre.x, 500.0);
+        assert_approx_eq!(centre.y, 500.0);
+    }
+}